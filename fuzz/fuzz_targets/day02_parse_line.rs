@@ -0,0 +1,23 @@
+#![no_main]
+use advent_of_code_2020_fuzz::day02::{CountInRange, EntryParser, ExactlyOnePosition, OutOfRange, Policy};
+use libfuzzer_sys::fuzz_target;
+
+// Every line of a password database, checked against both policies of the tasks
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let parser = EntryParser::new();
+        let policies: [&dyn Policy; 3] = [
+            &CountInRange,
+            &ExactlyOnePosition::new(OutOfRange::NoMatch),
+            &ExactlyOnePosition::new(OutOfRange::Error)
+        ];
+
+        for line in input.lines() {
+            if let Ok(entry) = parser.parse(line) {
+                for policy in policies.iter() {
+                    let _ = entry.check(*policy);
+                }
+            }
+        }
+    }
+});
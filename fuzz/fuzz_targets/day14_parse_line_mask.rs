@@ -0,0 +1,13 @@
+#![no_main]
+use advent_of_code_2020_fuzz::day14::{parse_line_mask, parse_line_memory};
+use libfuzzer_sys::fuzz_target;
+
+// Every line of a program, parsed as mask and as memory write
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        for line in input.lines() {
+            let _ = parse_line_mask(line);
+            let _ = parse_line_memory(line);
+        }
+    }
+});
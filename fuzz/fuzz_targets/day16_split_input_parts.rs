@@ -0,0 +1,10 @@
+#![no_main]
+use advent_of_code_2020_fuzz::day16::parse_notes;
+use libfuzzer_sys::fuzz_target;
+
+// A whole input file (split into its sections, then each rule and ticket parsed)
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = parse_notes(input);
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+use advent_of_code_2020_fuzz::day17::{parse_start_field, Neighbourhood, Rule};
+use libfuzzer_sys::fuzz_target;
+
+// The start slice in the lowest and highest supported amount of dimensions
+// (the whole input is also tried as rule)
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = parse_start_field::<2>(input, false, Rule::default(), Neighbourhood::Moore(1));
+        let _ = parse_start_field::<8>(input, true, Rule::default(), Neighbourhood::VonNeumann(1));
+        let _ = Rule::parse(input);
+    }
+});
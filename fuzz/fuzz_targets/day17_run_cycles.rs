@@ -0,0 +1,13 @@
+#![no_main]
+use advent_of_code_2020_fuzz::day17::{run, run_with_rule, Neighbourhood, Rule};
+use libfuzzer_sys::fuzz_target;
+
+// Plays a few cycles on the start slice, growing the field or failing with an error but never
+// panicking (the full neighbourhood in few and the small one in many dimensions)
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = run(input, 2, 2);
+        let _ = run_with_rule(input, 3, 1, Rule::default(), Neighbourhood::Moore(1));
+        let _ = run_with_rule(input, 8, 1, Rule::default(), Neighbourhood::VonNeumann(1));
+    }
+});
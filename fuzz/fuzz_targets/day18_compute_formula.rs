@@ -0,0 +1,15 @@
+#![no_main]
+use advent_of_code_2020_fuzz::day18::{compute_formula, compute_formula_big};
+use libfuzzer_sys::fuzz_target;
+
+// Every line of the homework with both sets of rules
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        for line in input.lines() {
+            for with_operator_precedence in [false, true].iter() {
+                let _ = compute_formula(line, *with_operator_precedence);
+                let _ = compute_formula_big(line, *with_operator_precedence);
+            }
+        }
+    }
+});
@@ -0,0 +1,10 @@
+#!/bin/sh
+# Copies the input files of the days into the corpora of their fuzz targets
+# (run from anywhere before `cargo fuzz run <target>`)
+cd "$(dirname "$0")"
+
+for target in day02_parse_line day14_parse_line_mask day16_split_input_parts day17_parse_start_field day17_run_cycles day18_compute_formula; do
+    day=${target%%_*}
+    mkdir -p "corpus/$target"
+    cp "../src/$day/input.txt" "corpus/$target/input.txt"
+done
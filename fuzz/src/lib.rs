@@ -0,0 +1,12 @@
+//! The parsers of the solutions, included from the main crate (which is only a binary)
+//! s.t. the fuzz targets can call them
+
+#[path = "../../src/files.rs"] pub mod files;
+#[path = "../../src/grid.rs"] pub mod grid;
+#[path = "../../src/point.rs"] pub mod point;
+#[path = "../../src/random.rs"] pub mod random;
+#[path = "../../src/day02/solution.rs"] pub mod day02;
+#[path = "../../src/day14/solution.rs"] pub mod day14;
+#[path = "../../src/day16/solution.rs"] pub mod day16;
+#[path = "../../src/day17/solution.rs"] pub mod day17;
+#[path = "../../src/day18/solution.rs"] pub mod day18;
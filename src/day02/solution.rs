@@ -1,89 +1,430 @@
-use crate::files::str_to_lines;
 use regex::Regex;
+use crate::random::Random;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 
-pub fn task1 () {
-    let data = get_input_data();
-    let lines = str_to_lines(data);
+/// One line of the password database, like `1-3 a: abcde´
+///
+/// How [first] and [second] are interpreted depends on the [Policy] the entry is checked against.
+/// Counts and positions always refer to characters (unicode scalar values, like rust´s `char´),
+/// neither to bytes nor to graphemes: `é´ written as `e´ + combining accent are two characters
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordEntry<'a> {
+    pub first: usize,
+    pub second: usize,
+    pub letter: char,
+    pub password: &'a str
+}
+
+impl<'a> PasswordEntry<'a> {
+    /// Parses a single line like `2-9 c: ccccccccc´
+    ///
+    /// use an [EntryParser] for many lines, s.t. the regex is compiled only once
+    pub fn parse(line: &'a str) -> Result<PasswordEntry<'a>, String> {
+        EntryParser::new().parse(line)
+    }
+
+    /// Checks whether the password complies with [policy]
+    ///
+    /// fails if the policy can't be applied to the entry
+    pub fn check(&self, policy: &dyn Policy) -> Result<bool, String> {
+        policy.check(self)
+    }
+}
 
-    // iterate over all characters of the password
-    let mut n_valid_passwords = 0;
+/// Parses lines of the password database (see [PasswordEntry])
+pub struct EntryParser {
+    re: Regex
+}
 
-    for line in lines {
-        let parsed_line = parse_line(&line);
-        let min = parsed_line.0;
-        let max = parsed_line.1;
+impl EntryParser {
+    pub fn new() -> Self {
+        // those (?P<name>) things generate a named match that we can extract later on
+        let re = Regex::new(r"^(?P<from>[0-9]+)-(?P<to>[0-9]+) (?P<needle>.): (?P<password>.+)$").expect("Regex not valid");
+        EntryParser { re }
+    }
 
-        // this basically extracts the first character of a &str
-        // (which only has exactly one anyways)
-        let needle = parsed_line.2.chars().next().unwrap();
-        let password = parsed_line.3;
+    /// Parses a line like `2-9 c: ccccccccc´
+    pub fn parse<'a>(&self, line: &'a str) -> Result<PasswordEntry<'a>, String> {
+        let matches = self.re.captures(line)
+            .ok_or_else(|| format!("`{}´ is not like `1-3 a: abcde´", line))?;
+        let parse_number = |name: &str| matches[name]
+            .parse::<usize>()
+            .map_err(|_| format!("number {} too large", &matches[name]));
 
+        return Ok(PasswordEntry {
+            first: parse_number("from")?,
+            second: parse_number("to")?,
+            // this basically extracts the first character of a &str
+            // (which only has exactly one anyways)
+            letter: matches["needle"].chars().next().unwrap(),
+            password: matches.name("password").unwrap().as_str()
+        })
+    }
+}
+
+/// The result of checking one line of a password database
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Valid,
+    Invalid,
+    /// the line is malformed or the policy can't be applied to it
+    Error(String)
+}
+
+/// How many lines of a password database got which [Verdict]
+#[derive(Debug, Clone, Default)]
+pub struct Totals {
+    pub n_lines: usize,
+    pub n_valid: usize,
+    pub n_invalid: usize,
+    pub n_errors: usize
+}
+
+/// A rule a password has to follow
+///
+/// Besides the rules of the tasks ([CountInRange] and [ExactlyOnePosition])
+/// any closure `Fn(&PasswordEntry) -> bool´ can be used as policy
+pub trait Policy {
+    /// Tells whether [entry] complies with the policy or why the policy can't be applied to it
+    fn check(&self, entry: &PasswordEntry) -> Result<bool, String>;
+}
+
+/// The rule of task 1: the letter has to appear at least [first] and at most [second] times
+pub struct CountInRange;
+
+impl Policy for CountInRange {
+    fn check(&self, entry: &PasswordEntry) -> Result<bool, String> {
         // iterate over all characters of the password
         let mut n_found = 0;
-        for char in password.chars() {
-            // and cound how often we meet [needle] in the [password]
-            if char == needle {
-                n_found+=1;
+        for char in entry.password.chars() {
+            // and cound how often we meet the letter in the password
+            if char == entry.letter {
+                n_found += 1;
             }
 
-            if n_found > max {
-                break;
+            if n_found > entry.second {
+                return Ok(false);
             }
         }
 
-        if n_found >= min && n_found <= max {
-            n_valid_passwords += 1;
-        }
+        Ok(n_found >= entry.first)
     }
+}
 
+/// What [ExactlyOnePosition] does with a position outside of the password
+/// (`0´ or behind its last character)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutOfRange {
+    /// the letter is not at that position
+    NoMatch,
+    /// the entry is reported as broken
+    Error
+}
+
+/// The rule of task 2: the letter has to be at exactly one of the positions [first] and [second]
+/// (counting characters from 1)
+pub struct ExactlyOnePosition {
+    pub out_of_range: OutOfRange
+}
+
+impl ExactlyOnePosition {
+    pub fn new(out_of_range: OutOfRange) -> Self {
+        ExactlyOnePosition { out_of_range }
+    }
+}
+
+impl Policy for ExactlyOnePosition {
+    fn check(&self, entry: &PasswordEntry) -> Result<bool, String> {
+        let is_at = |position: usize| -> Result<bool, String> {
+            // positions start at 1, so 0 can't be found (and must not underflow)
+            let found = match position {
+                0 => None,
+                _ => entry.password.chars().nth(position - 1)
+            };
+
+            match (found, self.out_of_range) {
+                (Some(letter), _) => Ok(letter == entry.letter),
+                (None, OutOfRange::NoMatch) => Ok(false),
+                (None, OutOfRange::Error) => Err(format!(
+                    "position {} is outside of the password `{}´ ({} characters)",
+                    position, entry.password, entry.password.chars().count()))
+            }
+        };
+
+        // if exactly one (not none and also not both -> xor(^)) times the letter appears
+        Ok(is_at(entry.first)? ^ is_at(entry.second)?)
+    }
+}
+
+impl<F: Fn(&PasswordEntry) -> bool> Policy for F {
+    fn check(&self, entry: &PasswordEntry) -> Result<bool, String> {
+        Ok(self(entry))
+    }
+}
+
+pub fn task1 () {
+    let n_valid_passwords = count_valid_passwords(get_input_data(), &CountInRange)
+        .unwrap_or_else(|report| panic!("Couldn't check the passwords:\n{}", report));
     println!("Valid passwords (Task 1): {}", n_valid_passwords);
 }
 
 pub fn task2 () {
+    let n_valid_passwords = count_valid_passwords(get_input_data(), &ExactlyOnePosition::new(OutOfRange::Error))
+        .unwrap_or_else(|report| panic!("Couldn't check the passwords:\n{}", report));
+    println!("Valid passwords (Task 2): {}", n_valid_passwords);
+}
+
+/// Checks the password database against the policies of both tasks and some additional ones
+pub fn task_audit() {
     let data = get_input_data();
-    let lines = str_to_lines(data);
 
-    // iterate over all characters of the password
-    let mut n_valid_passwords = 0;
+    let no_repeated_letter = |entry: &PasswordEntry| {
+        let chars: Vec<char> = entry.password.chars().collect();
+        chars.windows(2).all(|pair| pair[0] != pair[1])
+    };
+    let min_length = |entry: &PasswordEntry| entry.password.chars().count() >= 12;
+
+    let exactly_one_position = ExactlyOnePosition::new(OutOfRange::NoMatch);
+    let policies: Vec<(&str, &dyn Policy)> = vec![
+        ("count in range (task 1)", &CountInRange),
+        ("exactly one position (task 2)", &exactly_one_position),
+        ("no letter twice in a row", &no_repeated_letter),
+        ("at least 12 characters", &min_length)
+    ];
+
+    audit(data, &policies);
+}
 
-    for line in lines {
-        let parsed_line = parse_line(&line);
-        let first_pos = parsed_line.0;
-        let second_pos = parsed_line.1;
+/// Checks a password database with the policy called [policy_name] (`count´ or `position´)
+///
+/// The database is read line by line from the file [filename] (or stdin if there is none),
+/// s.t. files of any size can be checked. Prints the totals and, if [show_verdicts] is set,
+/// the verdict of every line.
+pub fn task_stream(policy_name: &str, filename: Option<&str>, show_verdicts: bool) -> Result<(), String> {
+    let policy: Box<dyn Policy> = match policy_name {
+        "count" => Box::new(CountInRange),
+        "position" => Box::new(ExactlyOnePosition::new(OutOfRange::Error)),
+        _ => return Err(format!("Unknown policy `{}´ (known are `count´ and `position´)", policy_name))
+    };
 
-        // this basically extracts the first character of a &str
-        // (which only has exactly one anyways)
-        let needle = parsed_line.2.chars().next().unwrap();
-        let password = parsed_line.3;
+    let reader: Box<dyn BufRead> = match filename {
+        Some(filename) => {
+            let file = File::open(filename).map_err(|err| format!("Couldn't open `{}´ due to `{}´", filename, err))?;
+            Box::new(BufReader::new(file))
+        },
+        None => Box::new(BufReader::new(std::io::stdin()))
+    };
 
-        // Now check the first and second position
-        // if exactly one (not none and also not both -> xor(^)) times the [needle] appears
-        if (password.chars().nth(first_pos as usize - 1).unwrap() == needle) ^ (password.chars().nth(second_pos as usize - 1).unwrap() == needle) {
-            n_valid_passwords += 1;
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut write_error = None;
+    let totals = validate_lines(reader, policy.as_ref(), |line_number, verdict| {
+        if !show_verdicts || write_error.is_some() {
+            return;
         }
+        let written = match verdict {
+            Verdict::Valid => writeln!(out, "line {}: valid", line_number),
+            Verdict::Invalid => writeln!(out, "line {}: invalid", line_number),
+            Verdict::Error(reason) => writeln!(out, "line {}: error, {}", line_number, reason)
+        };
+        write_error = written.err();
+    }).map_err(|err| format!("Couldn't read the passwords due to `{}´", err))?;
 
+    if let Some(err) = write_error {
+        return Err(format!("Couldn't write the verdicts due to `{}´", err));
     }
 
-    println!("Valid passwords (Task 2): {}", n_valid_passwords);
+    writeln!(out, "{} lines: {} valid, {} invalid, {} errors",
+             totals.n_lines, totals.n_valid, totals.n_invalid, totals.n_errors)
+        .map_err(|err| format!("Couldn't write the totals due to `{}´", err))
+}
+
+/// Lines longer than this many bytes (without their line break) are not read into memory
+/// but get an error verdict, s.t. a file without any line break can't eat up all memory
+const MAX_LINE_LENGTH: usize = 1 << 16;
+
+/// Checks every line read from [reader] against [policy] and calls [on_verdict]
+/// with the line number (starting at 1) and the verdict of each line
+///
+/// only one line (of at most [MAX_LINE_LENGTH] bytes) is kept in memory at a time, empty lines
+/// are skipped and a line which is too long or no valid UTF-8 gets an error verdict
+/// (only failing reads abort)
+pub fn validate_lines<R: BufRead, F: FnMut(usize, &Verdict)>(mut reader: R, policy: &dyn Policy, mut on_verdict: F) -> std::io::Result<Totals> {
+    let parser = EntryParser::new();
+    let mut totals = Totals::default();
+    let mut bytes = Vec::new();
+    let mut line_number = 0;
+
+    loop {
+        bytes.clear();
+        // the longest allowed line with its line break `\r\n´
+        let limit = MAX_LINE_LENGTH + 2;
+        if reader.by_ref().take(limit as u64).read_until(b'\n', &mut bytes)? == 0 {
+            break;
+        }
+        line_number += 1;
+
+        let cut_off = bytes.len() == limit && !bytes.ends_with(b"\n");
+        if cut_off {
+            skip_line(&mut reader)?;
+        }
+        let length = bytes.len() - bytes.iter().rev().take_while(|&&byte| byte == b'\n' || byte == b'\r').count();
+
+        let verdict = if cut_off || length > MAX_LINE_LENGTH {
+            Verdict::Error(format!("longer than {} bytes", MAX_LINE_LENGTH))
+        } else {
+            match std::str::from_utf8(&bytes[..length]) {
+                Ok(line) => {
+                    if line.is_empty() {
+                        continue;
+                    }
+
+                    match parser.parse(line).and_then(|entry| entry.check(policy)) {
+                        Ok(true) => Verdict::Valid,
+                        Ok(false) => Verdict::Invalid,
+                        Err(reason) => Verdict::Error(reason)
+                    }
+                },
+                Err(err) => Verdict::Error(format!("not valid UTF-8 ({})", err))
+            }
+        };
+
+        totals.n_lines += 1;
+        match verdict {
+            Verdict::Valid => totals.n_valid += 1,
+            Verdict::Invalid => totals.n_invalid += 1,
+            Verdict::Error(_) => totals.n_errors += 1
+        }
+        on_verdict(line_number, &verdict);
+    }
+
+    Ok(totals)
+}
+
+/// Drops the rest of the current line (including its line break) from [reader]
+fn skip_line<R: BufRead>(reader: &mut R) -> std::io::Result<()> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(());
+        }
+
+        match buffer.iter().position(|&byte| byte == b'\n') {
+            Some(end) => {
+                reader.consume(end + 1);
+                return Ok(());
+            },
+            None => {
+                let length = buffer.len();
+                reader.consume(length);
+            }
+        }
+    }
 }
 
+/// Prints how many passwords in [data] follow each of the named [policies]
+pub fn audit(data: &str, policies: &[(&str, &dyn Policy)]) {
+    let parser = EntryParser::new();
+    let entries: Vec<PasswordEntry> = data
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| parser.parse(line).unwrap_or_else(|reason| panic!("Couldn't parse the passwords: {}", reason)))
+        .collect();
 
-/// Parses a line like `2-9 c: ccccccccc´
-/// and returns (2, 9, 'c', 'ccccccccc')
-fn parse_line(line: &str) -> (u8, u8, &str, &str) {
-    // those (?P<name>) things generate a named match that we can extract later on
-    let re = Regex::new(r"^(?P<from>[0-9]+)-(?P<to>[0-9]+) (?P<needle>.): (?P<password>.+)$").expect("Regex not valid");
-    let matches = re.captures(line).expect(&format!("Regex did not match for line `{}´", line));
+    for (name, policy) in policies {
+        let verdicts: Vec<Result<bool, String>> = entries.iter().map(|entry| entry.check(*policy)).collect();
+        let n_valid = verdicts.iter().filter(|verdict| **verdict == Ok(true)).count();
+        let n_errors = verdicts.iter().filter(|verdict| verdict.is_err()).count();
+        println!("{:<30} {:>5} of {} passwords valid ({} not checkable)", name, n_valid, entries.len(), n_errors);
+    }
+}
+
+/// Counts the passwords in [data] which follow [policy]
+///
+/// fails with a line per entry which is malformed or the policy couldn't be applied to
+pub fn count_valid_passwords(data: &str, policy: &dyn Policy) -> Result<usize, String> {
+    let mut errors: Vec<String> = Vec::new();
+    let totals = validate_lines(data.as_bytes(), policy, |line_number, verdict| {
+        if let Verdict::Error(reason) = verdict {
+            errors.push(format!("line {}: {}", line_number, reason));
+        }
+    }).expect("Reading from memory can't fail");
+
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
 
-    return (matches["from"].parse::<u8>().unwrap(),
-            matches["to"].parse::<u8>().unwrap(),
-            matches.name("needle").unwrap().as_str(),
-            matches.name("password").unwrap().as_str()
-    )
+    Ok(totals.n_valid)
 }
 
 
+/// Generates a password database of [n_lines] lines like `1-3 a: abcde´ from [seed]
+///
+/// Both positions lie within the password, s.t. both policies of the tasks apply to every line
+pub fn generate_input(n_lines: usize, seed: u64) -> String {
+    let mut random = Random::new(seed);
+    let random_letter = |random: &mut Random| (b'a' + random.below(26) as u8) as char;
+
+    let mut input = String::new();
+    for _ in 0..n_lines {
+        let letter = random_letter(&mut random);
+        let first = random.between(1, 10);
+        let second = random.between(first + 1, 20);
+
+        // the letter is favoured s.t. a fair share of the passwords is valid
+        let length = random.between(second, 20);
+        let password: String = (0..length)
+            .map(|_| if random.one_in(3) { letter } else { random_letter(&mut random) })
+            .collect();
+
+        input.push_str(&format!("{}-{} {}: {}\n", first, second, letter, password));
+    }
+
+    input
+}
+
 /// Reads the file into the binary
 fn get_input_data() -> &'static str {
     return include_str!("input.txt");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_utf8_is_an_error_for_its_line_only() {
+        let input: &[u8] = b"1-3 a: abcde\n1-3 b: c\xffdefg\n\n2-9 c: ccccccccc\r\n";
+        let mut verdicts = Vec::new();
+        let totals = validate_lines(input, &CountInRange, |line_number, verdict| verdicts.push((line_number, verdict.clone())))
+            .unwrap();
+
+        assert_eq!(verdicts.len(), 3);
+        assert_eq!(verdicts[0], (1, Verdict::Valid));
+        match &verdicts[1] {
+            (2, Verdict::Error(reason)) => assert!(reason.starts_with("not valid UTF-8"), "{}", reason),
+            other => panic!("Expected an error in line 2, got {:?}", other)
+        }
+        assert_eq!(verdicts[2], (4, Verdict::Valid));
+        assert_eq!((totals.n_lines, totals.n_valid, totals.n_invalid, totals.n_errors), (3, 2, 0, 1));
+    }
+
+    #[test]
+    fn too_long_lines_are_errors_for_their_line_only() {
+        let longest = format!("1-3 a: {}", "a".repeat(MAX_LINE_LENGTH - 7));
+        let too_long = format!("1-3 a: {}", "a".repeat(MAX_LINE_LENGTH));
+        let input = format!("{}\r\n{}\n1-3 a: abcde\n{}", longest, too_long, too_long);
+        let mut verdicts = Vec::new();
+        validate_lines(input.as_bytes(), &CountInRange, |line_number, verdict| verdicts.push((line_number, verdict.clone())))
+            .unwrap();
+
+        let too_long_error = Verdict::Error(format!("longer than {} bytes", MAX_LINE_LENGTH));
+        assert_eq!(verdicts, vec![
+            (1, Verdict::Invalid),
+            (2, too_long_error.clone()),
+            (3, Verdict::Valid),
+            (4, too_long_error)
+        ]);
+    }
+}
@@ -1,92 +1,206 @@
-use crate::files::str_to_lines;
-
-pub fn task1() {
-    let data = str_to_lines(get_input_data());
-
-    let n_lines = data.len();
-    let n_cols = data[0].len();
+use crate::grid::Grid;
+use crate::random::Random;
+
+/// The slopes tried in task 2 as (dx, dy)
+const TASK2_SLOPES: [(i64, usize); 5] = [
+    (1, 1),
+    (3, 1),
+    (5, 1),
+    (7, 1),
+    (1, 2)
+];
+
+/// A slope (moving [dx] right and [dy] down each step) together with the trees hit on it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlopeResult {
+    pub dx: i64,
+    pub dy: usize,
+    pub n_trees: u64
+}
 
-    // steps in x (right) direction and y (down)-direction
-    let dx = 3;
-    let dy = 1;
+/// The map of the wood (`#´ is a tree, `.´ is open ground)
+///
+/// In x-direction the map repeats endlessly (to both sides)
+pub struct TreeMap {
+    /// [true] where a tree is
+    grid: Grid<bool>
+}
 
-    // current position
-    let mut pos_x = 0;
-    let mut pos_y = 0;
+impl TreeMap {
+    /// Parses a map like
+    ///
+    /// ```text
+    /// ..##.......
+    /// #...#...#..
+    /// ```
+    ///
+    /// fails if the rows are not equally wide or contain something else than `#´ and `.´
+    pub fn parse(input: &str) -> Result<TreeMap, String> {
+        let grid = Grid::parse(input, |field| match field {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None
+        })?;
+
+        Ok(TreeMap { grid })
+    }
 
-    // count of hit trees
-    let mut n_trees = 0;
+    pub fn get_width(&self) -> usize {
+        self.grid.get_width()
+    }
 
-    // We step until we are out of the y span of the wood
-    while pos_y < n_lines {
-        if data[pos_y].as_bytes()[pos_x] == '#' as u8  {
-            n_trees += 1;
-        }
+    pub fn get_height(&self) -> usize {
+        self.grid.get_height()
+    }
 
-        // move one step
-        pos_y += dy;
+    /// Whether there is a tree at row [y] and column [x]
+    /// ([x] may be any column left or right of the map, since it repeats)
+    pub fn is_tree(&self, x: i64, y: usize) -> bool {
+        *self.grid.get_wrapping(x, y as i64)
+    }
 
-        // in x-direction we pretend that the world is repeating
-        pos_x = (pos_x + dx) % n_cols;
+    /// Counts the trees hit when starting top left and moving [dx] right and [dy] down each step
+    /// until we are out of the y span of the wood
+    ///
+    /// a negative [dx] moves left, [dy] has to be positive
+    pub fn trees_on_slope(&self, dx: i64, dy: usize) -> u64 {
+        assert!(dy > 0, "The slope has to move down");
+
+        (0..self.get_height())
+            .step_by(dy)
+            .enumerate()
+            .filter(|(step, y)| self.is_tree(*step as i64 * dx, *y))
+            .count() as u64
     }
 
-    println!("Trees hit: {}", n_trees);
-}
+    /// Multiplies the trees hit on each of the [slopes] (as (dx, dy))
+    pub fn product_for_slopes(&self, slopes: &[(i64, usize)]) -> u64 {
+        slopes
+            .iter()
+            .map(|(dx, dy)| self.trees_on_slope(*dx, *dy))
+            .product()
+    }
 
-// Basically the same as [task1]
-pub fn task2() {
-    let data = str_to_lines(get_input_data());
-
-    let n_lines = data.len();
-    let n_cols = data[0].len();
-
-    // steps in x (right) direction and y (down)-direction
-    // here is the difference, we don't only try one solution but a list of solutions
-    let slopes = [
-        (1, 1),
-        (3, 1),
-        (5, 1),
-        (7, 1),
-        (1, 2)
-    ];
-
-    // the results for each attempt are collected here
-    let mut n_trees_hit: Vec<u64> = Vec::new();
-
-    for slope in slopes.iter() {
-        let dx = slope.0;
-        let dy = slope.1;
-
-        // current position
-        let mut pos_x = 0;
-        let mut pos_y = 0;
-
-        // count of hit trees
-        let mut n_trees: u64 = 0;
-
-        // We step until we are out of the y span of the wood
-        while pos_y < n_lines {
-            if data[pos_y].as_bytes()[pos_x] == '#' as u8  {
-                n_trees += 1;
+    /// Tries all slopes with `-max_step <= dx <= max_step´ and `1 <= dy <= max_step´
+    /// and returns the ones with the fewest and the most trees hit (in that order)
+    ///
+    /// on a tie the slope found first wins (smaller dy, then smaller dx)
+    pub fn find_extreme_slopes(&self, max_step: usize) -> (SlopeResult, SlopeResult) {
+        assert!(max_step > 0, "At least one step has to be allowed");
+
+        let mut fewest: Option<SlopeResult> = None;
+        let mut most: Option<SlopeResult> = None;
+
+        for dy in 1..=max_step {
+            for dx in -(max_step as i64)..=max_step as i64 {
+                let result = SlopeResult { dx, dy, n_trees: self.trees_on_slope(dx, dy) };
+
+                if fewest.map_or(true, |fewest| result.n_trees < fewest.n_trees) {
+                    fewest = Some(result);
+                }
+                if most.map_or(true, |most| result.n_trees > most.n_trees) {
+                    most = Some(result);
+                }
             }
+        }
 
-            // move one step
-            pos_y += dy;
+        (fewest.unwrap(), most.unwrap())
+    }
 
-            // in x-direction we pretend that the world is repeating
-            pos_x = (pos_x + dx) % n_cols;
+    /// Renders the map with the path of the slope ([dx], [dy]) on it,
+    /// like in the task description: `O´ where a tree is hit and `X´ where we land on open ground
+    ///
+    /// The map is repeated to the sides as often as the path needs
+    pub fn render_path(&self, dx: i64, dy: usize) -> String {
+        assert!(dy > 0, "The slope has to move down");
+
+        let width = self.get_width() as i64;
+        let n_steps = ((self.get_height() - 1) / dy) as i64;
+        let last_x = n_steps * dx;
+
+        // whole copies of the map from the leftmost to the rightmost one touched
+        let from_x = last_x.min(0).div_euclid(width) * width;
+        let to_x = (last_x.max(0).div_euclid(width) + 1) * width;
+
+        let mut rendered = String::new();
+        for (y, row) in self.grid.rows().enumerate() {
+            // the column we land on in this row (if we land in this row at all)
+            let landing_x = match y % dy {
+                0 => Some((y / dy) as i64 * dx),
+                _ => None
+            };
+
+            for x in from_x..to_x {
+                let is_tree = row[x.rem_euclid(width) as usize];
+                let field = match (landing_x == Some(x), is_tree) {
+                    (true, true) => 'O',
+                    (true, false) => 'X',
+                    (false, true) => '#',
+                    (false, false) => '.'
+                };
+                rendered.push(field);
+            }
+            rendered.push('\n');
         }
 
-        n_trees_hit.push(n_trees);
+        rendered
     }
+}
 
+pub fn task1() {
+    let map = parse_input_data();
+
+    println!("Trees hit: {}", map.trees_on_slope(3, 1));
+}
+
+// Basically the same as [task1]
+// here is the difference, we don't only try one slope but a list of slopes
+pub fn task2() {
+    let map = parse_input_data();
+
+    let n_trees_total: u64 = TASK2_SLOPES.iter().map(|(dx, dy)| map.trees_on_slope(*dx, *dy)).sum();
 
     // And the product of those hits per run is the solution for that task
     println!("Trees hit total: {}, Product (Solution): {}",
-             (&n_trees_hit).into_iter().sum::<u64>(),
-             (&n_trees_hit).into_iter().product::<u64>()
+             n_trees_total,
+             map.product_for_slopes(&TASK2_SLOPES)
              );
+}
+
+/// Searches the slopes with the fewest and the most trees hit
+/// (taking at most [max_step] fields in each direction per step)
+pub fn task_best_slope(max_step: usize) {
+    let map = parse_input_data();
+
+    let (fewest, most) = map.find_extreme_slopes(max_step);
+    println!("Fewest trees: {} (right {}, down {})", fewest.n_trees, fewest.dx, fewest.dy);
+    println!("Most trees: {} (right {}, down {})", most.n_trees, most.dx, most.dy);
+}
+
+/// Prints the map with the path of the slope ([dx], [dy]) on it
+pub fn task_render_path(dx: i64, dy: usize) {
+    let map = parse_input_data();
+
+    print!("{}", map.render_path(dx, dy));
+    println!("Trees hit: {}", map.trees_on_slope(dx, dy));
+}
+
+fn parse_input_data() -> TreeMap {
+    TreeMap::parse(get_input_data()).unwrap_or_else(|err| panic!("Couldn't parse the map: {}", err))
+}
+
+/// Generates a forest of [n_rows] rows (at least one, 31 fields wide like the task) from [seed]
+pub fn generate_input(n_rows: usize, seed: u64) -> String {
+    let mut random = Random::new(seed);
+
+    let mut input = String::new();
+    for _ in 0..n_rows.max(1) {
+        let row: String = (0..31).map(|_| if random.one_in(5) { '#' } else { '.' }).collect();
+        input.push_str(&row);
+        input.push('\n');
+    }
 
+    input
 }
 
 /// Reads the file into the binary
@@ -1,18 +1,20 @@
 use crate::files;
-use std::num;
-
-struct Point(i64, i64);
+use crate::point::Point;
+use crate::random::Random;
 
 struct Ship {
-    position: Point,
-    angle: i32
+    position: Point<2>,
+
+    /// The direction one step forward (`F1´) moves the ship
+    heading: Point<2>
 }
 
 impl Ship {
-    fn new(position: Option<Point>, angle: Option<i32>) -> Ship {
+    fn new(position: Option<Point<2>>, heading: Option<Point<2>>) -> Ship {
         Ship {
-            position: position.unwrap_or_else(|| Point (0,0)),
-            angle: angle.unwrap_or_else(|| 90),
+            position: position.unwrap_or_else(|| Point::origin()),
+            // facing east
+            heading: heading.unwrap_or_else(|| Point::new(1, 0)),
         }
     }
 
@@ -24,24 +26,13 @@ impl Ship {
         // println!("Command {}, distance {}", cmd, distance);
 
         match cmd {
-            "N" => self.position.1 += distance,
-            "S" => self.position.1 -= distance,
-            "W" => self.position.0 -= distance,
-            "E" => self.position.0 += distance,
-            "L" => {
-                self.angle = (self.angle - distance as i32);
-                if self.angle < 0 {self.angle+= 360}
-            },
-            "R" => self.move_ship(&format!("L{}", 360 - distance)),
-            "F" => {
-                match self.angle {
-                    90 => self.position.0 += distance,
-                    180 => self.position.1 -= distance,
-                    270 => self.position.0 -= distance,
-                    0 => self.position.1 += distance,
-                    _ => panic!("Illegal angle {}", self.angle)
-                }
-            },
+            "N" => self.position += Point::new(0, distance),
+            "S" => self.position -= Point::new(0, distance),
+            "W" => self.position -= Point::new(distance, 0),
+            "E" => self.position += Point::new(distance, 0),
+            "L" => self.heading = self.heading.rotate(distance).expect(&format!("Illegal angle {}", distance)),
+            "R" => self.heading = self.heading.rotate(-distance).expect(&format!("Illegal angle {}", distance)),
+            "F" => self.position += self.heading * distance,
             _ => panic!("Unknown command {}", instruction)
         }
 
@@ -62,9 +53,27 @@ pub fn task1() {
     }
 
     println!("Position of ship: {}, {}; Manhatten distance: {}",
-             ship.position.0,
-             ship.position.1,
-             ship.position.0.abs() + ship.position.1.abs())
+             ship.position.x(),
+             ship.position.y(),
+             ship.position.manhattan_distance(&Point::origin()))
+}
+
+/// Generates [n_instructions] navigation instructions like `F10´ or `R90´ from [seed]
+pub fn generate_input(n_instructions: usize, seed: u64) -> String {
+    let mut random = Random::new(seed);
+
+    let mut input = String::new();
+    for _ in 0..n_instructions {
+        // moving forward is the most common instruction
+        let command = *random.choose(&['N', 'S', 'E', 'W', 'L', 'R', 'F', 'F']);
+        let value = match command {
+            'L' | 'R' => *random.choose(&[90, 180, 270]),
+            _ => random.between(1, 100)
+        };
+        input.push_str(&format!("{}{}\n", command, value));
+    }
+
+    input
 }
 
 /// Reads the file into the binary
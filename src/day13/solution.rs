@@ -1,7 +1,8 @@
 use crate::files::{str_to_lines};
+use crate::math::chinese_remainder;
+use crate::random::Random;
 use std::iter::Map;
 use std::collections::HashMap;
-use num::integer::lcm;
 
 
 pub fn task1() {
@@ -62,91 +63,81 @@ pub fn task2() {
     let file_contents = get_input_data();
     let lines = str_to_lines(file_contents);
 
+    let offsets = parse_offsets(&lines[1]);
 
-    // We read al busses from the input (split by `,´)
-    let bus_ids: Vec<&str> = lines[1]
-        .split(',')
-        .into_iter()
-        .collect();
-
-    // The basic idea is to use the chinese remainder theorem to find a time
-    // where the different busses are one minute apart each (while interval 'x') does not matter)
+    let (a, lcm) = find_earliest_timestamp(&offsets)
+        .expect("The busses never depart in that order");
 
-    // Save the highest bus id (only relevant for the naive solution
-    // since it will be our smallest increment for brute force
-    let mut highest_bus_id: u64 = 0;
+    println!("Smallest time offset: {:?}", a);
+    println!("LCM: {:?}", lcm);
+}
 
-    // Build a lookup which is points from bus_id => position (index) within the input list
-    // s.t. [10, 3, x, 5] would be (10 => 0, 3=>1, 5=>3)
+/// Builds a lookup which is points from bus_id => position (index) within the list of busses
+/// (split by `,´) s.t. `10,3,x,5´ would be (10 => 0, 3 => 1, 5 => 3)
+fn parse_offsets(line: &str) -> HashMap<u64, u64> {
     let mut offsets = HashMap::<u64, u64>::new();
-    for (i, bus_id) in bus_ids.into_iter().enumerate() {
+    for (i, bus_id) in line.split(',').into_iter().enumerate() {
         if bus_id != "x" {
             let current_bus_id: u64 = bus_id.parse::<u64>().expect("Couldn't parse bus id");
-            if current_bus_id > highest_bus_id {
-                highest_bus_id = current_bus_id;
-            }
-            offsets.insert(current_bus_id,
-                           i as u64);
+            offsets.insert(current_bus_id, i as u64);
         }
     }
 
-    // We will use the chinese remainder theorem
+    offsets
+}
+
+/// Finds the earliest time where each bus departs `offset´ minutes after it (for all
+/// (bus_id => offset) of [offsets]).
+///
+/// returns the time together with the least common multiple of the bus ids (the time span
+/// after which the busses repeat that order) or [None] if they never depart in that order
+pub fn find_earliest_timestamp(offsets: &HashMap<u64, u64>) -> Option<(i128, i128)> {
+    // The basic idea is to use the chinese remainder theorem to find a time
+    // where the different busses are one minute apart each (while interval 'x') does not matter)
     // this finds a `a´ where `a = x_n mod m_n´ holds for all x_n and m_n
     let mut moduli: Vec<i128> = Vec::new();         // stores all the m_n
     let mut remainders: Vec<i128> = Vec::new();     // stores all the x_n
 
     // each bus should come each separated by one minute times their list position
-    // so the moduli is the bus_id (=interval) and the remainder is the list position
-    for (bus_id, offset) in (&offsets).into_iter() {
+    // so the moduli is the bus_id (=interval) and the remainder is minus the list position
+    // (the bus departs `offset´ minutes after `a´)
+    for (bus_id, offset) in offsets.iter() {
         moduli.push((*bus_id) as i128);
-        remainders.push((*offsets.get(bus_id).unwrap()) as i128);
+        remainders.push(-(*offset as i128));
     }
 
-    // Calculate the chinese remainder
-    let a = chinese_remainder(
-        remainders.as_slice(),
-        moduli.as_slice(),
-    );
-
-    // bad news: the chinese reminder doesn't necessrily find the smallest solution
-    // However if we find the least common multiply (lcm) we can reduce the solution
-    // to its smallest version bei calculating lcm % a
-    let lcm = (&moduli).into_iter().fold(
-        moduli[0], |a, b| lcm(a.clone(), b.clone()));
-
-    println!("Smallest time offset: {:?}", lcm % a.unwrap());
-    println!("LCM: {:?}", lcm);
-    println!("Modulus {:?}", moduli);
-    println!("Remainders {:?}", remainders);
-
-    /*
-    // Naive solution (Does calculate way too slow for bigger inputs)
-    // Will likely take a day or so to find it
-
-    let mut run_id: u64 = 1;
-    let offset_of_highest_bus_id = offsets.get(&highest_bus_id)
-        .expect("Couldn't find highest bus id in map");
-
-    loop {
-        let current_offset = run_id * highest_bus_id - *offset_of_highest_bus_id;
+    // The chinese remainder is the smallest solution already
+    // (all others are further multiples of the least common multiple (lcm) of the moduli away)
+    chinese_remainder(remainders.as_slice(), moduli.as_slice())
+}
 
-        let mut all_offsets_are_0 = true;
-        for (bus_id, offset) in (&offsets).into_iter() {
-            all_offsets_are_0 &= ((current_offset + offset) % bus_id) == 0;
-        }
+/// Same as [find_earliest_timestamp] by trying every departure of the bus with the highest id,
+/// which calculates way too slow for bigger inputs (would likely take a day or so for the task).
+///
+/// Gives up (returns [None]) once the time reaches [limit]
+pub fn find_earliest_timestamp_naive(offsets: &HashMap<u64, u64>, limit: u64) -> Option<u64> {
+    // the highest bus id will be our smallest increment for brute force
+    let (highest_bus_id, offset_of_highest_bus_id) = offsets
+        .iter()
+        .max_by_key(|(bus_id, _)| **bus_id)
+        .map(|(bus_id, offset)| (*bus_id, *offset))?;
+
+    // the first time the bus with the highest id departs `offset´ minutes after
+    let mut current_offset = (highest_bus_id - offset_of_highest_bus_id % highest_bus_id) % highest_bus_id;
+
+    while current_offset < limit {
+        let all_offsets_are_0 = offsets
+            .iter()
+            .all(|(bus_id, offset)| (current_offset + offset) % bus_id == 0);
 
         if all_offsets_are_0 {
-            println!("Current offset {}", current_offset);
-            break;
-        }
-
-        if run_id % 10000 == 0 {
-            println!("Current offset (run #{}): {}", run_id, current_offset)
+            return Some(current_offset);
         }
 
-        run_id += 1;
+        current_offset += highest_bus_id;
     }
-    */
+
+    None
 }
 
 /// calculates the time in minutes we would have to wait (beginning from [arrival_time] until
@@ -159,41 +150,64 @@ fn get_waiting_time(arrival_time: u64, bus_id: u64) -> u64 {
     bus_id - rest_to_arrival_time
 }
 
-/// Reads the file into the binary
-fn get_input_data() -> &'static str {
-    return include_str!("input.txt");
-}
-
-
-
-/// Chinese reminder theorem calculations
-fn chinese_remainder(residues: &[i128], modulii: &[i128]) -> Option<i128> {
-    let prod = modulii.iter().product::<i128>();
-
-    let mut sum = 0;
-
-    for (&residue, &modulus) in residues.iter().zip(modulii) {
-        let p = prod / modulus;
-        sum += residue * mod_inv(p, modulus)? * p
+/// Generates a bus schedule with [n_busses] busses (at least one, and some `x´ in between) from [seed]
+///
+/// The bus ids are distinct primes (so they are coprime and task 2 always has a solution).
+/// There are less busses if the product of their ids wouldn't fit into an [i128] anymore
+pub fn generate_input(n_busses: usize, seed: u64) -> String {
+    let mut random = Random::new(seed);
+
+    let mut primes: Vec<u64> = (7..1000).filter(|n| (2..*n).take_while(|d| d * d <= *n).all(|d| n % d != 0)).collect();
+    random.shuffle(&mut primes);
+
+    let mut bus_ids: Vec<String> = Vec::new();
+    let mut product: i128 = 1;
+    for bus_id in primes.into_iter().take(n_busses.max(1)) {
+        product = match product.checked_mul(bus_id as i128) {
+            Some(product) => product,
+            None => break
+        };
+
+        if !bus_ids.is_empty() {
+            for _ in 0..random.below(5) {
+                bus_ids.push(String::from("x"));
+            }
+        }
+        bus_ids.push(bus_id.to_string());
     }
 
-    Some(sum % prod)
+    format!("{}\n{}\n", random.between(100000, 10000000), bus_ids.join(","))
 }
 
-fn mod_inv(x: i128, n: i128) -> Option<i128> {
-    let (g, x, _) = egcd(x, n);
-    if g == 1 {
-        Some((x % n + n) % n)
-    } else {
-        None
-    }
+/// Reads the file into the binary
+fn get_input_data() -> &'static str {
+    return include_str!("input.txt");
 }
 
-fn egcd(a: i128, b: i128) -> (i128, i128, i128) {
-    if a == 0 {
-        (b, 0, 1)
-    } else {
-        let (g, x, y) = egcd(b % a, a);
-        (g, y - (b / a) * x, x)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn earliest_timestamp_agrees_with_naive(bus_ids in prop::collection::vec(prop::option::of(1u64..40), 1..6)) {
+            let line: Vec<String> = bus_ids
+                .iter()
+                .map(|bus_id| bus_id.map_or(String::from("x"), |id| id.to_string()))
+                .collect();
+            let offsets = parse_offsets(&line.join(","));
+
+            // every solution repeats after the lcm, so the naive search can stop there
+            let lcm = offsets.keys().fold(1u64, |lcm, bus_id| num::integer::lcm(lcm, *bus_id));
+            let expected = find_earliest_timestamp_naive(&offsets, lcm.max(1));
+
+            let actual = find_earliest_timestamp(&offsets).map(|(a, _)| a as u64);
+            if offsets.is_empty() {
+                prop_assert_eq!(actual, Some(0));
+            } else {
+                prop_assert_eq!(actual, expected);
+            }
+        }
     }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use crate::files::str_to_lines;
+use crate::random::Random;
 use regex::Regex;
 use num::range;
 
@@ -20,10 +21,10 @@ pub fn task1() {
     // Execute program
     for (i, line) in lines.iter().enumerate() {
       if line.starts_with("mask") {
-          let (current_mask_1, current_mask_0, _) = parse_line_mask(line.as_str());
+          let (current_mask_1, current_mask_0, _) = parse_line_mask(line.as_str()).expect("Couldn't parse mask");
 
       } else {
-          let (index, value) = parse_line_memory(line.as_str());
+          let (index, value) = parse_line_memory(line.as_str()).expect("Couldn't parse instruction");
           let effective_value = apply_masks_to_value(value,
                                                      &current_mask_1,
                                                      &current_mask_0);
@@ -39,7 +40,110 @@ pub fn task1() {
 
 pub fn task2() {
     let input = get_input_data();
-    let lines = str_to_lines(input);
+
+    println!("Sum of memory: {}", sum_memory_floating(input).expect("Couldn't run the program"));
+}
+
+/// A set of memory addresses, namely all the ones which match [fixed] on all bits which are 0
+/// in [floating] (the bits set in [floating] may be 0 or 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FloatingAddress {
+    /// the bits which are not floating, all floating bits are 0 here
+    fixed: u64,
+    floating: u64
+}
+
+impl FloatingAddress {
+    fn new(address: u64, floating: u64) -> FloatingAddress {
+        FloatingAddress { fixed: address & !floating, floating }
+    }
+
+    /// The amount of addresses in this set
+    fn size(&self) -> u64 {
+        1 << self.floating.count_ones()
+    }
+
+    /// Removes all addresses of [other] from this set
+    ///
+    /// The rest is split into disjoint sets, one for each floating bit of this set
+    /// which is fixed in [other] (at most)
+    fn subtract(&self, other: &FloatingAddress) -> Vec<FloatingAddress> {
+        // if they differ on a bit neither of them may change, they don't share any address
+        let fixed_in_both = !self.floating & !other.floating;
+        if (self.fixed ^ other.fixed) & fixed_in_both != 0 {
+            return vec![*self];
+        }
+
+        // Split off the half (of what is left) which differs from [other] on such a bit and go
+        // on with the half matching it. In the end all bits match, which lies within [other]
+        let mut rest = Vec::new();
+        let mut current = *self;
+        let bits_to_split = self.floating & !other.floating;
+        for index in 0..64 {
+            let bit = (1 as u64) << index;
+            if bits_to_split & bit == 0 {
+                continue;
+            }
+
+            let floating = current.floating & !bit;
+            rest.push(FloatingAddress { fixed: (current.fixed & !bit) | (!other.fixed & bit), floating });
+            current = FloatingAddress { fixed: (current.fixed & !bit) | (other.fixed & bit), floating };
+        }
+
+        rest
+    }
+}
+
+/// Runs the program of task 2 (where the masks modify the memory addresses) and returns
+/// the sum of all values left in memory
+///
+/// Instead of writing every single address we go backwards through the writes: each write
+/// only counts for the addresses which are not overwritten later on
+///
+/// fails if a line can't be parsed or the sum doesn't fit into 64 bits
+pub fn sum_memory_floating(program: &str) -> Result<u64, String> {
+    // (addresses, value) of all writes in order
+    let mut writes: Vec<(FloatingAddress, u64)> = Vec::new();
+
+    let mut current_floating_mask: u64 = 0;
+    let mut current_set_to_one_mask: u64 = 0;
+    for line in str_to_lines(program).iter() {
+        if line.starts_with("mask") {
+            let masks = parse_line_mask(line.as_str())?;
+            current_set_to_one_mask = masks.0;
+            current_floating_mask = masks.2;
+        } else {
+            let (memory_address, value) = parse_line_memory(line.as_str())?;
+            writes.push((FloatingAddress::new(memory_address | current_set_to_one_mask, current_floating_mask),
+                         value));
+        }
+    }
+
+    let mut sum: u64 = 0;
+    let mut overwritten: Vec<FloatingAddress> = Vec::new();
+    for (addresses, value) in writes.into_iter().rev() {
+        let mut remaining = vec![addresses];
+        for later in overwritten.iter() {
+            remaining = remaining.iter().flat_map(|part| part.subtract(later)).collect();
+        }
+
+        let n_addresses: u64 = remaining.iter().map(|part| part.size()).sum();
+        sum = value
+            .checked_mul(n_addresses)
+            .and_then(|written| sum.checked_add(written))
+            .ok_or("The sum of memory doesn't fit into 64 bits")?;
+        overwritten.push(addresses);
+    }
+
+    Ok(sum)
+}
+
+/// Same as [sum_memory_floating] but expands each floating address into all its addresses and
+/// writes every single one into a memory map (2^k writes for k floating bits)
+///
+/// simple enough to be obviously right, so [sum_memory_floating] is checked against it
+pub fn sum_memory_floating_naive(program: &str) -> Result<u64, String> {
+    let lines = str_to_lines(program);
 
     // This map stores the memory of our machine
     let mut memory: HashMap<u64, u64> = HashMap::new();
@@ -50,16 +154,15 @@ pub fn task2() {
     // Those will have to be BOTH, 1 and 0
     let mut current_set_to_one_mask: u64 = 0;
 
-    for (i, line) in lines.iter().enumerate() {
-        println!("Processing line {}: `{}´", i, line);
+    for line in lines.iter() {
         if line.starts_with("mask") { // Parse a mask line
 
-            let masks = parse_line_mask(line.as_str());
+            let masks = parse_line_mask(line.as_str())?;
             current_set_to_one_mask = masks.0;
             current_floating_mask = masks.2;
 
         } else {
-            let index_and_value = parse_line_memory(line.as_str());
+            let index_and_value = parse_line_memory(line.as_str())?;
             let mut memory_address = index_and_value.0;
             let value = index_and_value.1;
 
@@ -75,7 +178,11 @@ pub fn task2() {
 
         }
     }
-    println!("Sum of memory: {}", memory.values().sum::<u64>());
+
+    memory
+        .values()
+        .try_fold(0 as u64, |sum, value| sum.checked_add(*value))
+        .ok_or(String::from("The sum of memory doesn't fit into 64 bits"))
 }
 
 /// The idea here is that each true bit in the [floating_mask] will
@@ -140,15 +247,17 @@ fn apply_masks_to_value (value: u64, mask_1: &u64, mask_0: &u64) -> u64 {
 /// Will parse a line like `mem[8] = 12´
 ///
 /// returns for the given example: `(8, 12)´
-fn parse_line_memory(line: &str) -> (u64, u64) {
+pub fn parse_line_memory(line: &str) -> Result<(u64, u64), String> {
 
     // Build a regex which parses the mem[<memory>] = <value>
     // parts from the line
-    let regex = Regex::new(r"mem\[(?P<memory>\d+)\] = (?P<value>\d+)").unwrap();
-    let captures = regex.captures(line).expect(&format!("Couldn't parse instruction {}", line));
+    let regex = Regex::new(r"^mem\[(?P<memory>[0-9]+)\] = (?P<value>[0-9]+)$").unwrap();
+    let captures = regex.captures(line.trim()).ok_or_else(|| format!("Couldn't parse instruction `{}´", line))?;
+    let parse_number = |name: &str| captures[name]
+        .parse::<u64>()
+        .map_err(|_| format!("Number {} too large in instruction `{}´", &captures[name], line));
 
-    return (captures["memory"].parse::<u64>().unwrap(),
-            captures["value"].parse::<u64>().unwrap());
+    return Ok((parse_number("memory")?, parse_number("value")?));
 }
 
 /// Will return three bit masks:
@@ -158,31 +267,97 @@ fn parse_line_memory(line: &str) -> (u64, u64) {
 /// (3) positions of X
 ///
 /// i.e., 100X1 => (10001, 01100, 00010)
-/// only the 34 least significant bits are relevant
-fn parse_line_mask(line: &str) -> (u64, u64, u64){
+/// the mask (after `mask = ´) has to consist of exactly 36 bits
+pub fn parse_line_mask(line: &str) -> Result<(u64, u64, u64), String> {
+    let mask = line
+        .trim()
+        .strip_prefix("mask = ")
+        .ok_or_else(|| format!("Couldn't parse mask `{}´", line))?;
+    if mask.len() != 36 {
+        return Err(format!("The mask `{}´ has {} instead of 36 bits", mask, mask.len()));
+    }
+
     let mut mask_1 : u64 = 0;
     let mut mask_0 : u64 = 0;
     let mut mask_x : u64 = 0;
     let mut current_exponent = 36;
 
-    for char in line.bytes().skip(7) {
-        if char == '1' as u8 {
-            mask_1 |= (1 as u64) << (current_exponent - 1)
-        } else if char == '0' as u8 {
-            mask_0 |= (1 as u64) << (current_exponent - 1)
-        } else if char == 'X' as u8 {
-            mask_x |= (1 as u64) << (current_exponent - 1)
-
+    for char in mask.chars() {
+        match char {
+            '1' => mask_1 |= (1 as u64) << (current_exponent - 1),
+            '0' => mask_0 |= (1 as u64) << (current_exponent - 1),
+            'X' => mask_x |= (1 as u64) << (current_exponent - 1),
+            _ => return Err(format!("Unknown bit `{}´ in mask `{}´", char, mask))
         }
 
         current_exponent -= 1;
     }
 
-    return (mask_1, mask_0, mask_x);
+    return Ok((mask_1, mask_0, mask_x));
 }
 
 
+/// The most floating bits a mask of [generate_input] has (each one doubles the written addresses)
+const MAX_FLOATING_BITS: usize = 9;
+
+/// Generates a program of [n_lines] lines from [seed]: masks (with at most [MAX_FLOATING_BITS]
+/// floating bits), each followed by some memory writes
+pub fn generate_input(n_lines: usize, seed: u64) -> String {
+    let mut random = Random::new(seed);
+
+    let mut input = String::new();
+    let mut n_writes_left = 0;
+    for _ in 0..n_lines {
+        if n_writes_left == 0 {
+            let mut bits: Vec<char> = (0..36).map(|_| if random.one_in(2) { '1' } else { '0' }).collect();
+            let mut positions: Vec<usize> = (0..36).collect();
+            random.shuffle(&mut positions);
+            for position in positions.into_iter().take(random.below(MAX_FLOATING_BITS as u64 + 1) as usize) {
+                bits[position] = 'X';
+            }
+
+            input.push_str(&format!("mask = {}\n", bits.into_iter().collect::<String>()));
+            n_writes_left = random.between(1, 6);
+        } else {
+            input.push_str(&format!("mem[{}] = {}\n", random.below(65536), random.between(1, 999999999)));
+            n_writes_left -= 1;
+        }
+    }
+
+    input
+}
+
 /// Reads the file into the binary
 fn get_input_data() -> &'static str {
     return include_str!("input.txt");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// A mask with floating bits only within the lowest 6 bits (at most 64 addresses per write)
+    fn mask_line() -> impl Strategy<Value = String> {
+        prop::collection::vec(prop::sample::select(vec!['0', '1', 'X']), 6)
+            .prop_map(|low_bits| format!("mask = {}{}", "0".repeat(30), low_bits.into_iter().collect::<String>()))
+    }
+
+    /// Writes to few addresses, s.t. they often overlap
+    fn memory_line() -> impl Strategy<Value = String> {
+        (0u64..64, 0u64..1000).prop_map(|(address, value)| format!("mem[{}] = {}", address, value))
+    }
+
+    fn program() -> impl Strategy<Value = String> {
+        (mask_line(), prop::collection::vec(prop_oneof![mask_line(), memory_line(), memory_line()], 0..12))
+            .prop_map(|(first_mask, lines)| format!("{}\n{}", first_mask, lines.join("\n")))
+    }
+
+    proptest! {
+        #[test]
+        fn sum_memory_floating_agrees_with_naive(program in program()) {
+            prop_assert_eq!(sum_memory_floating(&program), sum_memory_floating_naive(&program));
+            prop_assert!(sum_memory_floating(&program).is_ok());
+        }
+    }
 }
\ No newline at end of file
@@ -7,15 +7,127 @@ pub fn task1(n_rounds: Option<usize>) {
 
     // Extract amount of rounds if we get some, we default to 2002 otherwise
     let rounds = n_rounds.unwrap_or(2020);
-    let data = get_input_data();
+    let starting_numbers = parse_starting_numbers(get_input_data());
+
+    println!("Last inserted number: {}", play(&starting_numbers, rounds));
+}
+
+
+pub fn task2() {
+    // Task 2 is COMPLETELY the same as task i
+    // Our solution is fast enough to do 15s (in release mode at least)
+    task1(Some(30000000));
+}
+
+/// Plays the game for [n_rounds] turns and returns the number spoken in the last turn
+///
+/// Only remembers the last turn each number was spoken in (as array indexed by the number),
+/// since no spoken number can be larger than the amount of turns
+pub fn play(starting_numbers: &[u64], n_rounds: usize) -> u64 {
+    assert!(!starting_numbers.is_empty(), "Need at least one starting number");
+    assert!(n_rounds > 0, "Need at least one turn");
+    if n_rounds <= starting_numbers.len() {
+        return starting_numbers[n_rounds - 1];
+    }
+
+    // turns fitting into u32 need only half of the memory
+    if n_rounds <= u32::MAX as usize {
+        play_with_turns::<u32>(starting_numbers, n_rounds)
+    } else {
+        play_with_turns::<usize>(starting_numbers, n_rounds)
+    }
+}
+
+/// A turn as stored by [play_with_turns] (0 if the number was never spoken)
+trait Turn: Copy {
+    const NEVER: Self;
+
+    /// [turn] must fit into the type
+    fn from_turn(turn: usize) -> Self;
+    fn to_turn(self) -> usize;
+}
+
+impl Turn for u32 {
+    const NEVER: u32 = 0;
+
+    fn from_turn(turn: usize) -> u32 {
+        turn as u32
+    }
+
+    fn to_turn(self) -> usize {
+        self as usize
+    }
+}
+
+impl Turn for usize {
+    const NEVER: usize = 0;
+
+    fn from_turn(turn: usize) -> usize {
+        turn
+    }
+
+    fn to_turn(self) -> usize {
+        self
+    }
+}
+
+/// Same as [play] for more than the starting numbers, with each turn stored as [T]
+/// (which must fit [n_rounds])
+fn play_with_turns<T: Turn>(starting_numbers: &[u64], n_rounds: usize) -> u64 {
+    // the turn (starting at 1) each number was spoken in last, 0 if it never was.
+    // the number of the latest turn is not stored yet but kept in [last_number]
+    let mut last_turns: Vec<T> = vec![T::NEVER; n_rounds];
+
+    // numbers from [n_rounds] on can only be starting numbers (later ones are distances of
+    // two turns), they don't get into the array, s.t. its size doesn't depend on them
+    let mut large_last_turns: HashMap<u64, usize> = HashMap::new();
+    for (i, &number) in starting_numbers[..starting_numbers.len() - 1].iter().enumerate() {
+        if number < n_rounds as u64 {
+            last_turns[number as usize] = T::from_turn(i + 1);
+        } else {
+            large_last_turns.insert(number, i + 1);
+        }
+    }
+
+    // the last starting number is the only large one which is looked up
+    let first_turn = starting_numbers.len();
+    let last_start = *starting_numbers.last().unwrap();
+    let previous_turn = if last_start < n_rounds as u64 {
+        let previous_turn = last_turns[last_start as usize].to_turn();
+        last_turns[last_start as usize] = T::from_turn(first_turn);
+        previous_turn
+    } else {
+        large_last_turns.get(&last_start).copied().unwrap_or(0)
+    };
+
+    let mut last_number = if previous_turn == 0 { 0 } else { first_turn - previous_turn };
+    for turn in first_turn + 1..n_rounds {
+        // [turn] is the turn [last_number] was spoken in
+        let previous_turn = last_turns[last_number].to_turn();
+        last_turns[last_number] = T::from_turn(turn);
+        last_number = if previous_turn == 0 { 0 } else { turn - previous_turn };
+    }
+
+    last_number as u64
+}
+
+/// Same as [play] but keeps a map from each spoken number to the list of all turns it was spoken in
+///
+/// slower and needs far more memory, but simple enough to check [play] against
+pub fn play_with_history(starting_numbers: &[u64], n_rounds: usize) -> u64 {
+    assert!(!starting_numbers.is_empty(), "Need at least one starting number");
+    assert!(n_rounds > 0, "Need at least one turn");
+    if n_rounds <= starting_numbers.len() {
+        return starting_numbers[n_rounds - 1];
+    }
 
     // creates our initial memory
-    let initial_input = create_initial_memory(data);
+    let initial_input = create_initial_memory(starting_numbers);
     let mut memory = initial_input.0;
     let mut last_inserted_number = initial_input.1;
 
     // now do the turns
-    for i in range(memory.keys().len() + 1,rounds + 1) {
+    for i in range(starting_numbers.len() + 1, n_rounds + 1) {
         // println!("Turn: {}", i);
         let was_new = memory.get(&last_inserted_number).unwrap().len() == 1;
         if was_new {
@@ -42,14 +154,7 @@ pub fn task1(n_rounds: Option<usize>) {
         }
     }
 
-    println!("Last inserted number: {}", last_inserted_number);
-}
-
-
-pub fn task2() {
-    // Task 2 is COMPLETELY the same as task i
-    // Our solution is fast enough to do 15s (in release mode at least)
-    task1(Some(30000000));
+    last_inserted_number
 }
 
 /// Inserts a value into the memory at point [value] with value [position_in_list]
@@ -69,11 +174,15 @@ fn insert_into_memory(memory: &mut HashMap<u64, Vec<u64>>, value: u64, position_
     t.push(position_in_list);
 }
 
-/// Loads the input file and persists the data into memory
+/// Parses the starting numbers like `0,3,6´
+fn parse_starting_numbers(data: &str) -> Vec<u64> {
+    data.trim().split(',').into_iter().map(|c| c.trim().parse::<u64>().expect("Could not parse input")).collect()
+}
+
+/// Persists the starting numbers into memory
 ///
 /// returns the memory and the last inserted value
-fn create_initial_memory(data: &str) -> (HashMap<u64, Vec<u64>>, u64) {
-    let initial_numbers: Vec<u64> = data.split(',').into_iter().map(|c| c.parse::<u64>().expect("Could not parse input")).collect();
+fn create_initial_memory(initial_numbers: &[u64]) -> (HashMap<u64, Vec<u64>>, u64) {
     let mut memory :HashMap<u64, Vec<u64>> = HashMap::new();
     let mut last_inserted_value: u64 = 0;
     for (i, number) in initial_numbers.iter().enumerate() {
@@ -88,4 +197,34 @@ fn create_initial_memory(data: &str) -> (HashMap<u64, Vec<u64>>, u64) {
 /// Reads the file into the binary
 fn get_input_data() -> &'static str {
     return include_str!("input.txt");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn play_agrees_with_history(starting_numbers in prop::collection::vec(starting_number(), 1..6), n_rounds in 1usize..2000) {
+            prop_assert_eq!(play(&starting_numbers, n_rounds), play_with_history(&starting_numbers, n_rounds));
+        }
+    }
+
+    /// Mostly small starting numbers, but some larger than any amount of turns
+    fn starting_number() -> impl Strategy<Value = u64> {
+        prop_oneof![
+            4 => 0u64..20,
+            1 => Just(1_000_000_000_000u64),
+            1 => Just(u64::max_value())
+        ]
+    }
+
+    #[test]
+    fn large_starting_numbers_are_played() {
+        assert_eq!(play(&[1_000_000_000_000, 0], 10), play_with_history(&[1_000_000_000_000, 0], 10));
+        assert_eq!(play(&[u64::max_value(), 3, u64::max_value()], 4), 2);
+        assert_eq!(play(&[u64::max_value(), 3, u64::max_value()], 5), 0);
+        assert_eq!(play(&[u64::max_value()], 3), 0);
+    }
 }
\ No newline at end of file
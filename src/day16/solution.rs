@@ -1,20 +1,55 @@
-use crate::files::str_to_lines;
 use regex::Regex;
 use num::range;
 use std::collections::HashMap;
+use crate::random::Random;
 
 /// Complex type which consists of a rule name and its two numeric boundaries as defined in the task
 type Rule<'a> = (&'a str, (u64, u64), (u64, u64));
 
+/// The sections of the input file as raw lines (headers and blank lines removed)
+struct InputParts<'a> {
+    rules: Vec<&'a str>,
+    own_ticket: &'a str,
+    nearby_tickets: Vec<&'a str>,
+}
+
+/// The parsed input file (see [parse_notes])
+pub struct Notes<'a> {
+    rules: Vec<Rule<'a>>,
+    own_ticket: Vec<u64>,
+    nearby_tickets: Vec<Vec<u64>>,
+}
+
+/// How [report] should print its findings
+pub enum ReportFormat {
+    Table,
+    Json
+}
+
+/// A value of a nearby ticket where none of the rules apply
+///
+/// Also remembers the range which came closest to the value and by how much it was missed
+struct InvalidValue<'a> {
+    column: usize,
+    value: u64,
+    nearest_rule: &'a str,
+    nearest_range: (u64, u64),
+    misses_by: u64,
+}
+
+/// All invalid values of a nearby ticket (the ticket number starts at 1)
+struct InvalidTicket<'a> {
+    ticket: usize,
+    values: Vec<InvalidValue<'a>>,
+}
+
 pub fn task1() {
 
     // Read the data
     let input = get_input_data();
 
-    // And split it into their sections (rules, own ticket, nearby tickets)
-    let parts = split_input_parts(input);
-    let rules: Vec<Rule> = parts[0].iter().map(|r| parse_rule(r)).collect();
-    let nearby_tickets: Vec<Vec<u64>> = parts[2].iter().map(|t| parse_ticket(t)).collect();
+    // And parse their sections (rules, own ticket, nearby tickets)
+    let Notes { rules, nearby_tickets, .. } = parse_notes(input).expect("Couldn't parse the input");
 
     let mut fail_sum: u64 = 0;
     // find all tickets which are not valid and sum the invalid columns thereof
@@ -33,10 +68,7 @@ pub fn task2() {
     // same as task [task1]
     let input = get_input_data();
 
-    let parts = split_input_parts(input);
-    let rules: Vec<Rule> = parts[0].iter().map(|r| parse_rule(r)).collect();
-    let nearby_tickets: Vec<Vec<u64>> = parts[2].iter().map(|t| parse_ticket(t)).collect();
-    let own_ticket: Vec<u64> = parse_ticket(&parts[1][0]);
+    let Notes { rules, own_ticket, nearby_tickets } = parse_notes(input).expect("Couldn't parse the input");
 
     // Since our ticket may decide also in the assignment of columns to ticket entries
     // we will have to take that into account also
@@ -45,11 +77,193 @@ pub fn task2() {
         .filter(|ticket| get_invalid_rules_for_ticket(ticket, &rules).len() == 0)
         .collect();
 
+    // Stores the final assignment in the form of `column -> rule_name´
+    // i.e., assigns each column a matching rule
+    let col_to_rule_name = assign_columns_to_rules(&rules, &valid_tickets, own_ticket.len());
+
+    // No we just need to find all columns which have `departure´ in their name
+    let departure_columns: Vec<usize> = col_to_rule_name
+        .into_iter()
+        .filter(|entry| entry.1.starts_with("departure"))
+        .map(|entry| entry.0).collect();
+
+    // and multiply the values of those columns within the own ticket
+    let res = departure_columns
+        .into_iter()
+        .fold(1 as u64, | prev, col| prev * own_ticket.get(col).unwrap());
+
+
+    println!("Product of fields: {}", res);
+}
+
+/// Prints why nearby tickets were rejected and what our own ticket says
+///
+/// For each invalid nearby ticket all values which fit no rule are listed together with
+/// the range they miss by the smallest amount. After assigning the columns, our own ticket
+/// is printed as `field name: value´ pairs
+pub fn report(format: ReportFormat) {
+    let input = get_input_data();
+
+    let Notes { rules, own_ticket, nearby_tickets } = parse_notes(input).expect("Couldn't parse the input");
+
+    // Collect the invalid tickets with their explanations
+    let mut invalid_tickets: Vec<InvalidTicket> = Vec::new();
+    for (i, ticket) in nearby_tickets.iter().enumerate() {
+        let values = get_invalid_rules_for_ticket(ticket, &rules)
+            .into_iter()
+            .map(|column| explain_invalid_value(column, ticket[column], &rules))
+            .collect::<Option<Vec<InvalidValue>>>();
+        let values = match values {
+            Some(values) => values,
+            None => {
+                // without rules every value is invalid, but nothing can tell why
+                match format {
+                    ReportFormat::Table => println!("The notes contain no rules"),
+                    ReportFormat::Json => println!("{{\"error\": \"no rules\"}}")
+                }
+                return;
+            }
+        };
+
+        if !values.is_empty() {
+            invalid_tickets.push(InvalidTicket { ticket: i + 1, values });
+        }
+    }
+
+    let scanning_error_rate: u64 = invalid_tickets
+        .iter()
+        .flat_map(|ticket| ticket.values.iter())
+        .map(|value| value.value)
+        .sum();
+
+    // Same as in [task2] the assignment is done only on the valid tickets
+    let valid_tickets: Vec<Vec<u64>> = nearby_tickets
+        .into_iter()
+        .filter(|ticket| get_invalid_rules_for_ticket(ticket, &rules).len() == 0)
+        .collect();
+    let col_to_rule_name = assign_columns_to_rules(&rules, &valid_tickets, own_ticket.len());
+
+    // The decoded own ticket in column order `(field name, value)´
+    // (columns which couldn't be assigned are shown as `?´)
+    let decoded_ticket: Vec<(String, u64)> = own_ticket
+        .iter()
+        .enumerate()
+        .map(|(column, value)| (
+            col_to_rule_name.get(&column).cloned().unwrap_or_else(|| String::from("?")),
+            *value))
+        .collect();
+
+    match format {
+        ReportFormat::Table => print_report_table(&invalid_tickets, scanning_error_rate, &decoded_ticket),
+        ReportFormat::Json => print_report_json(&invalid_tickets, scanning_error_rate, &decoded_ticket),
+    }
+}
+
+/// Finds the range (of all [rules]) which is closest to [value]
+///
+/// returns [None] if there are no [rules]
+fn explain_invalid_value<'a>(column: usize, value: u64, rules: &Vec<Rule<'a>>) -> Option<InvalidValue<'a>> {
+    let mut nearest: Option<InvalidValue> = None;
+
+    for rule in rules {
+        for range in [rule.1, rule.2].iter() {
+            let misses_by = if value < range.0 {
+                range.0 - value
+            } else if value > range.1 {
+                value - range.1
+            } else {
+                0
+            };
+
+            if nearest.is_none() || nearest.as_ref().unwrap().misses_by > misses_by {
+                nearest = Some(InvalidValue {
+                    column,
+                    value,
+                    nearest_rule: rule.0,
+                    nearest_range: *range,
+                    misses_by
+                });
+            }
+        }
+    }
+
+    return nearest;
+}
+
+/// Prints the report as human readable table
+fn print_report_table(invalid_tickets: &Vec<InvalidTicket>, scanning_error_rate: u64, decoded_ticket: &Vec<(String, u64)>) {
+    println!("Invalid nearby tickets: {}", invalid_tickets.len());
+    println!("{:>6} | {:>6} | {:>6} | {:<20} | {:<11} | {:>9}",
+             "ticket", "column", "value", "nearest rule", "range", "misses by");
+    println!("{}", "-".repeat(74));
+
+    for ticket in invalid_tickets {
+        for value in ticket.values.iter() {
+            println!("{:>6} | {:>6} | {:>6} | {:<20} | {:<11} | {:>9}",
+                     ticket.ticket,
+                     value.column,
+                     value.value,
+                     value.nearest_rule,
+                     format!("{}-{}", value.nearest_range.0, value.nearest_range.1),
+                     value.misses_by);
+        }
+    }
+
+    println!("Scanning error rate: {}", scanning_error_rate);
+    println!();
+    println!("Our ticket:");
+    for (name, value) in decoded_ticket {
+        println!("  {}: {}", name, value);
+    }
+}
+
+/// Prints the report as a JSON document
+fn print_report_json(invalid_tickets: &Vec<InvalidTicket>, scanning_error_rate: u64, decoded_ticket: &Vec<(String, u64)>) {
+    let tickets_json: Vec<String> = invalid_tickets
+        .iter()
+        .map(|ticket| {
+            let values_json: Vec<String> = ticket.values
+                .iter()
+                .map(|value| format!(
+                    "{{\"column\": {}, \"value\": {}, \"nearest_rule\": \"{}\", \"nearest_range\": [{}, {}], \"misses_by\": {}}}",
+                    value.column,
+                    value.value,
+                    escape_json(value.nearest_rule),
+                    value.nearest_range.0,
+                    value.nearest_range.1,
+                    value.misses_by))
+                .collect();
+
+            format!("    {{\"ticket\": {}, \"invalid_values\": [{}]}}", ticket.ticket, values_json.join(", "))
+        })
+        .collect();
+
+    let own_ticket_json: Vec<String> = decoded_ticket
+        .iter()
+        .map(|(name, value)| format!("    {{\"field\": \"{}\", \"value\": {}}}", escape_json(name), value))
+        .collect();
+
+    println!("{{");
+    println!("  \"scanning_error_rate\": {},", scanning_error_rate);
+    println!("  \"invalid_tickets\": [\n{}\n  ],", tickets_json.join(",\n"));
+    println!("  \"own_ticket\": [\n{}\n  ]", own_ticket_json.join(",\n"));
+    println!("}}");
+}
+
+/// Escapes quotes and backslashes s.t. [value] can be put into a JSON string
+fn escape_json(value: &str) -> String {
+    return value.replace('\\', "\\\\").replace('"', "\\\"");
+}
+
+/// Finds out which column belongs to which rule by looking at the [valid_tickets]
+///
+/// returns the assignment in the form of `column -> rule_name´
+fn assign_columns_to_rules(rules: &Vec<Rule>, valid_tickets: &Vec<Vec<u64>>, n_columns: usize) -> HashMap<usize, String> {
     // Stores a lookup table in the form of `rule -> [columns_where_rules_apply]´
     let mut rules_to_valid_columns: HashMap<String, Vec<usize>> = HashMap::new();
 
     // for each column find all matching rules (i.e, where all rows apply to rule)
-    for column_num in range(0, own_ticket.len()) {
+    for column_num in range(0, n_columns) {
         for rule in rules.iter() {
             // println!("Check {} for column {}", rule_name, column_num);
             let rule_name = rule.0;
@@ -76,7 +290,6 @@ pub fn task2() {
     }
 
     // Stores the final assignment in the form of `column -> rule_name´
-    // i.e., assigns each column a matching rule
     let mut col_to_rule_name: HashMap<usize, String> = HashMap::new();
 
     // Loop until no rule is left or we reach an error condition
@@ -96,19 +309,7 @@ pub fn task2() {
                                   column_for_rule);
     }
 
-    // No we just need to find all columns which have `departure´ in their name
-    let departure_columns: Vec<usize> = col_to_rule_name
-        .into_iter()
-        .filter(|entry| entry.1.starts_with("departure"))
-        .map(|entry| entry.0).collect();
-
-    // and multiply the values of those columns within the own ticket
-    let res = departure_columns
-        .into_iter()
-        .fold(1 as u64, | prev, col| prev * own_ticket.get(col).unwrap());
-
-
-    println!("Product of fields: {}", res);
+    return col_to_rule_name;
 }
 
 /// checks if the [rule] matches [value]
@@ -144,34 +345,96 @@ fn find_rule_with_only_one_valid_column (rules_to_valid_columns: &HashMap<String
 ///     - first part are the rules,
 ///     - seconds part is the own ticket
 ///     - third part are the other peoples' tickets
-fn split_input_parts(input: &str) -> Vec<Vec<String>> {
-    let parts = input.split("\r\n\r\n");
+///
+/// Sections are found by their headers (`your ticket:´ and `nearby tickets:´), so line
+/// endings (LF or CRLF), trailing whitespace and additional blank lines do not matter
+fn split_input_parts(input: &str) -> Result<InputParts<'_>, String> {
+    let mut rules: Vec<&str> = Vec::new();
+    let mut own_ticket: Option<Vec<&str>> = None;
+    let mut nearby_tickets: Option<Vec<&str>> = None;
+
+    // Which section the current line belongs to (0: rules, 1: own ticket, 2: nearby tickets)
+    let mut section = 0;
+
+    // `lines´ already strips `\n´ as well as `\r\n´
+    for line in input.lines().map(|line| line.trim()) {
+        if line.is_empty() {
+            continue;
+        }
 
-    let mut line_parts: Vec<Vec<String>> = Vec::new();
+        match line {
+            "your ticket:" => {
+                if own_ticket.is_some() {
+                    return Err(String::from("Found section `your ticket:´ twice"));
+                }
+                own_ticket = Some(Vec::new());
+                section = 1;
+            },
+            "nearby tickets:" => {
+                if nearby_tickets.is_some() {
+                    return Err(String::from("Found section `nearby tickets:´ twice"));
+                }
+                nearby_tickets = Some(Vec::new());
+                section = 2;
+            },
+            _ => {
+                let target = match section {
+                    1 => own_ticket.as_mut().unwrap(),
+                    2 => nearby_tickets.as_mut().unwrap(),
+                    _ => &mut rules
+                };
+                target.push(line);
+            }
+        }
+    }
 
-    for (i, part) in parts.into_iter().enumerate() {
-        let lines = str_to_lines(part);
+    let own_ticket = own_ticket.ok_or("Missing section `your ticket:´")?;
+    let nearby_tickets = nearby_tickets.ok_or("Missing section `nearby tickets:´")?;
 
-        if i == 1 || i == 2 {
-            line_parts.insert(i, lines.into_iter().skip(1).collect());
-        } else {
-            line_parts.insert(i,lines);
-        }
+    if own_ticket.len() != 1 {
+        return Err(format!("Expected exactly one own ticket, found {}", own_ticket.len()));
+    }
+
+    return Ok(InputParts { rules, own_ticket: own_ticket[0], nearby_tickets });
+}
+
+/// Parses the whole input file (see [split_input_parts])
+///
+/// fails on any rule or ticket which can't be parsed and on tickets with another amount of
+/// values than our own ticket
+pub fn parse_notes(input: &str) -> Result<Notes<'_>, String> {
+    let parts = split_input_parts(input)?;
+
+    let rules = parts.rules
+        .iter()
+        .map(|r| parse_rule(r))
+        .collect::<Result<Vec<Rule>, String>>()?;
+    let own_ticket = parse_ticket(parts.own_ticket)?;
+    let nearby_tickets = parts.nearby_tickets
+        .iter()
+        .map(|t| parse_ticket(t))
+        .collect::<Result<Vec<Vec<u64>>, String>>()?;
+
+    if let Some((i, ticket)) = nearby_tickets.iter().enumerate().find(|(_, ticket)| ticket.len() != own_ticket.len()) {
+        return Err(format!("Nearby ticket {} has {} values but our own ticket has {}", i + 1, ticket.len(), own_ticket.len()));
     }
 
-    return line_parts;
+    return Ok(Notes { rules, own_ticket, nearby_tickets });
 }
 
 
 /// splits a line like `wagon: 35-898 or 907-957´ into ("wagon", (35, 898), (907, 857))
-fn parse_rule(rule_line: &str) -> Rule {
-    let re = Regex::new(r"^(?P<name>[a-zA-Z ]+): (?P<from1>\d+)-(?P<to1>\d+) or (?P<from2>\d+)-(?P<to2>\d+)$").expect("Invalid regex");
-    let captures = re.captures(rule_line).expect(&format!("Couldn't apply regex to {}", rule_line));
-
-    return (captures.name("name").unwrap().as_str(),
-            (captures.name("from1").unwrap().as_str().parse::<u64>().unwrap(), captures.name("to1").unwrap().as_str().parse::<u64>().unwrap()),
-            (captures.name("from2").unwrap().as_str().parse::<u64>().unwrap(), captures.name("to2").unwrap().as_str().parse::<u64>().unwrap()),
-    );
+fn parse_rule(rule_line: &str) -> Result<Rule, String> {
+    let re = Regex::new(r"^(?P<name>[a-zA-Z ]+): (?P<from1>[0-9]+)-(?P<to1>[0-9]+) or (?P<from2>[0-9]+)-(?P<to2>[0-9]+)$").expect("Invalid regex");
+    let captures = re.captures(rule_line).ok_or_else(|| format!("Couldn't apply regex to {}", rule_line))?;
+    let parse_number = |name: &str| captures.name(name).unwrap().as_str()
+        .parse::<u64>()
+        .map_err(|_| format!("Number {} too large in rule {}", &captures[name], rule_line));
+
+    return Ok((captures.name("name").unwrap().as_str(),
+               (parse_number("from1")?, parse_number("to1")?),
+               (parse_number("from2")?, parse_number("to2")?),
+    ));
 }
 
 /// Gets all indices of [ticket] where none(!) of [rules] applies
@@ -195,11 +458,136 @@ fn get_invalid_rules_for_ticket(ticket: &Vec<u64>, rules: &Vec<Rule>) -> Vec<usi
 }
 
 /// Parses a line like `7,3,47´ to `vec![7,3,47]`
-fn parse_ticket(rule_line: &str) -> Vec<u64> {
-    return rule_line.split(",").into_iter().map(|num| num.parse::<u64>().unwrap()).collect();
+fn parse_ticket(rule_line: &str) -> Result<Vec<u64>, String> {
+    return rule_line
+        .split(",")
+        .into_iter()
+        .map(|num| num.parse::<u64>().map_err(|_| format!("Couldn't parse `{}´ in ticket {}", num, rule_line)))
+        .collect();
+}
+
+/// The fields of the tickets [generate_input] creates
+const GENERATED_FIELDS: [&str; 20] = [
+    "departure location", "departure station", "departure platform", "departure track",
+    "departure date", "departure time", "arrival location", "arrival station", "arrival platform",
+    "arrival track", "class", "duration", "price", "route", "row", "seat", "train", "type",
+    "wagon", "zone"
+];
+
+/// Generates notes with [n_nearby_tickets] nearby tickets (at least one) from [seed]
+///
+/// The ranges are nested: each field allows all values of the fields before it (in a random
+/// order) and more. Since the first nearby ticket uses values only the field of its column
+/// allows, the columns can be assigned like in [task2]. About a quarter of the other nearby
+/// tickets has a value no rule allows
+pub fn generate_input(n_nearby_tickets: usize, seed: u64) -> String {
+    let mut random = Random::new(seed);
+    let n_fields = GENERATED_FIELDS.len();
+
+    // the bounds of the i-th field, widening with i
+    let mut lows: Vec<u64> = (26..=400).collect();
+    let mut highs: Vec<u64> = (500..=974).collect();
+    random.shuffle(&mut lows);
+    random.shuffle(&mut highs);
+    let mut lows: Vec<u64> = lows.into_iter().take(n_fields).collect();
+    let mut highs: Vec<u64> = highs.into_iter().take(n_fields).collect();
+    lows.sort_by(|a, b| b.cmp(a));
+    highs.sort();
+
+    // the column of the i-th field
+    let mut columns: Vec<usize> = (0..n_fields).collect();
+    random.shuffle(&mut columns);
+
+    let mut rules: Vec<String> = (0..n_fields)
+        .map(|i| {
+            let split = random.between(lows[i], highs[i] - 1);
+            format!("{}: {}-{} or {}-{}", GENERATED_FIELDS[i], lows[i], split, split + 1, highs[i])
+        })
+        .collect();
+    random.shuffle(&mut rules);
+
+    let valid_ticket = |random: &mut Random, distinct: bool| {
+        let mut ticket = vec![0; n_fields];
+        for i in 0..n_fields {
+            ticket[columns[i]] = if distinct && i > 0 {
+                random.between(highs[i - 1] + 1, highs[i])
+            } else {
+                random.between(lows[i], highs[i])
+            };
+        }
+        ticket
+    };
+    let to_line = |ticket: Vec<u64>| ticket.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(",");
+
+    let own_ticket = to_line(valid_ticket(&mut random, false));
+    let mut nearby_tickets: Vec<String> = Vec::new();
+    for i in 0..n_nearby_tickets.max(1) {
+        let mut ticket = valid_ticket(&mut random, i == 0);
+        if i > 0 && random.one_in(4) {
+            let column = random.below(n_fields as u64) as usize;
+            ticket[column] = if random.one_in(2) {
+                random.between(1, lows[n_fields - 1] - 1)
+            } else {
+                random.between(highs[n_fields - 1] + 1, 999)
+            };
+        }
+        nearby_tickets.push(to_line(ticket));
+    }
+
+    format!("{}\n\nyour ticket:\n{}\n\nnearby tickets:\n{}\n", rules.join("\n"), own_ticket, nearby_tickets.join("\n"))
 }
 
 /// Reads the file into the binary
 fn get_input_data() -> &'static str {
     return include_str!("input.txt");
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTES: &str = "class: 1-3 or 5-7\nrow: 6-11 or 33-44\n\nyour ticket:\n7,1\n\nnearby tickets:\n7,3\n40,4\n";
+
+    fn check_parts(input: &str) {
+        let parts = split_input_parts(input).unwrap();
+        assert_eq!(parts.rules, vec!["class: 1-3 or 5-7", "row: 6-11 or 33-44"]);
+        assert_eq!(parts.own_ticket, "7,1");
+        assert_eq!(parts.nearby_tickets, vec!["7,3", "40,4"]);
+    }
+
+    #[test]
+    fn splits_lf_input() {
+        check_parts(NOTES);
+        check_parts(NOTES.trim_end());
+    }
+
+    #[test]
+    fn splits_crlf_input() {
+        check_parts(&NOTES.replace("\n", "\r\n"));
+    }
+
+    #[test]
+    fn splits_input_with_surplus_blank_lines() {
+        check_parts(&format!("\n\n{}\n\n", NOTES.replace("\n\n", "\n\n\n  \n")));
+        check_parts(&NOTES.replace("\n\n", "\n").replace("\n", "\r\n\r\n"));
+    }
+
+    #[test]
+    fn invalid_values_are_explained_by_the_nearest_range() {
+        let notes = parse_notes(NOTES).unwrap();
+        let explained = explain_invalid_value(1, 45, &notes.rules).unwrap();
+        assert_eq!((explained.nearest_rule, explained.nearest_range, explained.misses_by), ("row", (33, 44), 1));
+
+        let no_rules = parse_notes("your ticket:\n7,1\n\nnearby tickets:\n7,3\n").unwrap();
+        assert!(no_rules.rules.is_empty());
+        assert!(explain_invalid_value(0, 7, &no_rules.rules).is_none());
+    }
+
+    #[test]
+    fn missing_or_doubled_sections_are_errors() {
+        assert_eq!(split_input_parts("class: 1-3 or 5-7\n\nnearby tickets:\n7,3\n").err(),
+                   Some(String::from("Missing section `your ticket:´")));
+        assert_eq!(split_input_parts(&format!("{}\nyour ticket:\n7,1\n", NOTES)).err(),
+                   Some(String::from("Found section `your ticket:´ twice")));
+    }
+}
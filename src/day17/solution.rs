@@ -1,316 +1,1018 @@
+use crate::grid::Grid;
+use crate::point::{Point, PointsInBox};
+use crate::random::Random;
 use num::range;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
 
 /// Since we have a n dimensions we do not want to fix dimensions in stuff like x,y,z but rather
-/// in infinite dimensions stored into [IndexType]
-#[derive(Debug)]
-struct IndexType(Vec::<i64>);
+/// work on points with any amount [N] of dimensions (the field is generic over it, see [for_dimensions])
+type IndexType<const N: usize> = Point<N>;
 
-// We want to use the [IndexType] as a String index
-//
-// returns format as `x.y.z´
-impl ToString for IndexType {
-    fn to_string(&self) -> String {
-
-        // We optimize this call a bit by some const transformations for 3 and 4 dims
-        let len = self.0.len();
-        if len == 3 {
-            return format!("{}.{}.{}", &self.0[0], &self.0[1], &self.0[2]);
-        } else if len == 4 {
-            return format!("{}.{}.{}.{}", &self.0[0], &self.0[1], &self.0[2], &self.0[3]);
+/// Calls the function generic over the amount of dimensions with [n_dims] as amount of dimensions,
+/// like `for_dimensions!(n_dims, run_field(input, 6))´ calls `run_field::<4>(input, 6)´ for 4 dimensions
+///
+/// the function has to return a [Result] whose error can be created from [UnsupportedDimensions],
+/// which is returned for any other amount of dimensions
+macro_rules! for_dimensions {
+    ($n_dims:expr, $function:ident ( $($argument:expr),* )) => {
+        match $n_dims {
+            2 => $function::<2>($($argument),*),
+            3 => $function::<3>($($argument),*),
+            4 => $function::<4>($($argument),*),
+            5 => $function::<5>($($argument),*),
+            6 => $function::<6>($($argument),*),
+            7 => $function::<7>($($argument),*),
+            8 => $function::<8>($($argument),*),
+            n_dims => Err(UnsupportedDimensions(n_dims).into())
         }
+    };
+}
+
+/// Error for an amount of dimensions [for_dimensions] can't handle
+struct UnsupportedDimensions(usize);
+
+impl From<UnsupportedDimensions> for String {
+    fn from(error: UnsupportedDimensions) -> Self {
+        format!("{} dimensions are not supported (only {} to {})", error.0, SLICE_DIMS, MAX_DIMS)
+    }
+}
+
+impl From<UnsupportedDimensions> for std::io::Error {
+    fn from(error: UnsupportedDimensions) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, String::from(error))
+    }
+}
+
+/// An [IndexType] packed into a single integer (see [pack])
+type PackedIndex = u64;
+
+/// Hashes a [PackedIndex] by a single multiplication
+///
+/// The default (cryptographically strong) hasher is only slowing us down here. The low bits of
+/// a product only depend on the low bits of the index (i.e., the first dimension), but the hash
+/// map picks its buckets by the low bits, so the well mixed middle bits are rotated down
+#[derive(Default)]
+struct PackedIndexHasher(u64);
+
+impl Hasher for PackedIndexHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
 
-        self.0.iter().map(|s| s.to_string()).collect::<Vec<String>>().join(".")
-        /* Iterative variant
-        let mut s = String::new();
-        for i in &self.0 {
-            s += &format!("{}.", i);
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_u64(*byte as u64);
         }
+    }
 
-        s.remove(s.len() - 1);
-        s*/
+    fn write_u64(&mut self, value: u64) {
+        self.0 = (self.0.rotate_left(5) ^ value).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
     }
 }
 
+type PackedSet = HashSet<PackedIndex, BuildHasherDefault<PackedIndexHasher>>;
+type PackedMap<V> = HashMap<PackedIndex, V, BuildHasherDefault<PackedIndexHasher>>;
+
+/// Least amount of bits each dimension gets within a [PackedIndex]
+const MIN_BITS_PER_DIM: usize = 8;
 
-/// We want to create a String in form of `1.4.2´ form [vec![1,4,2]]
-impl From<String> for IndexType {
-    fn from(s: String) -> Self {
-        Self (s.split(".").map(|s| s.parse::<i64>().unwrap()).collect::<Vec<i64>>())
+/// Maximum amount of dimensions fitting into a [PackedIndex]
+const MAX_DIMS: usize = 64 / MIN_BITS_PER_DIM;
+
+/// Bits used for each of the [N] dimensions within a [PackedIndex] (the fewer dimensions, the
+/// larger the field may grow)
+fn bits_per_dim<const N: usize>() -> usize {
+    64 / N
+}
+
+/// Coordinates are stored with this bias s.t. they are never negative within a [PackedIndex]
+fn coordinate_bias<const N: usize>() -> i64 {
+    1 << (bits_per_dim::<N>() - 1)
+}
+
+fn min_coordinate<const N: usize>() -> i64 {
+    -coordinate_bias::<N>()
+}
+
+fn max_coordinate<const N: usize>() -> i64 {
+    coordinate_bias::<N>() - 1
+}
+
+/// Packs [index] into a single integer where each dimension gets [bits_per_dim] bits
+/// (first dimension in the lowest bits)
+fn pack<const N: usize>(index: &IndexType<N>) -> PackedIndex {
+    let mut packed: PackedIndex = 0;
+    for (dim, value) in index.0.iter().enumerate() {
+        assert!(*value >= min_coordinate::<N>() && *value <= max_coordinate::<N>(), "Coordinate {} out of range", value);
+        packed |= ((value + coordinate_bias::<N>()) as u64) << (dim * bits_per_dim::<N>());
     }
+
+    packed
 }
 
-/// Stores our current field
-/// where each field is a mapping in form of `2.2.3´ => true|false
-struct Field {
-    field: HashMap<String, bool>
+/// Reverts [pack]
+fn unpack<const N: usize>(packed: PackedIndex) -> IndexType<N> {
+    let mut index = Point::origin();
+    for dim in 0..N {
+        index[dim] = unpack_dimension::<N>(packed, dim);
+    }
+
+    index
 }
 
+/// Extracts the coordinate of dimension [dim] from a packed index
+fn unpack_dimension<const N: usize>(packed: PackedIndex, dim: usize) -> i64 {
+    let bits = bits_per_dim::<N>();
+    ((packed >> (dim * bits)) & (u64::MAX >> (64 - bits))) as i64 - coordinate_bias::<N>()
+}
 
-/// Some functions are implemented for a 3d and a 4d field alike
-/// however, they could be adapted to work with a nd field, if you are keen enough
-impl Field {
-    fn new() -> Self {
-        return Field {
-            field: HashMap::new()
+/// Which indices around an index count as its neighbours
+#[derive(Debug, Clone, Copy)]
+pub enum Neighbourhood {
+    /// All indices differing by at most the radius in every dimension (the puzzle uses a radius of 1)
+    Moore(i64),
+
+    /// All indices with a manhattan distance of at most the radius
+    VonNeumann(i64)
+}
+
+impl Neighbourhood {
+    fn get_radius(&self) -> i64 {
+        match self {
+            Neighbourhood::Moore(radius) => *radius,
+            Neighbourhood::VonNeumann(radius) => *radius
         }
     }
 
-    fn get_field_value(&self, index: &IndexType) -> bool {
-        let index = index.to_string();
-        if !self.field.contains_key(&index) {
-            return false;
+    /// Creates the offsets from an index to all of its neighbours in [N] dimensions
+    fn get_offsets<const N: usize>(&self) -> Vec<IndexType<N>> {
+        let radius = self.get_radius();
+        Point::offsets_within(radius)
+            .into_iter()
+            .filter(|offset| match self {
+                Neighbourhood::Moore(_) => true,
+                Neighbourhood::VonNeumann(_) => offset.manhattan_distance(&Point::origin()) <= radius
+            })
+            .collect()
+    }
+}
+
+/// A life-like rule in B/S notation like `B3/S23´
+///
+/// i.e., an inactive index becomes active with 3 active neighbours and an active index
+/// stays active with 2 or 3 active neighbours. Since higher dimensions can have more than 9
+/// neighbours the counts may also be separated by commas (`B3,12/S2,3´)
+#[derive(Debug, Clone)]
+pub struct Rule {
+    birth: Vec<u32>,
+    survival: Vec<u32>
+}
+
+impl Rule {
+    /// Parses a rule like `B3/S23´ (case does not matter and the parts may be swapped)
+    pub fn parse(rule: &str) -> Result<Rule, String> {
+        let mut birth: Option<Vec<u32>> = None;
+        let mut survival: Option<Vec<u32>> = None;
+
+        for part in rule.trim().split('/') {
+            let mut chars = part.trim().chars();
+            let target = match chars.next() {
+                Some('B') | Some('b') => &mut birth,
+                Some('S') | Some('s') => &mut survival,
+                _ => return Err(format!("Rule part `{}´ has to start with `B´ or `S´", part))
+            };
+
+            if target.is_some() {
+                return Err(format!("Rule part `{}´ appears twice", part));
+            }
+            *target = Some(Rule::parse_counts(chars.as_str())?);
+        }
+
+        let birth = birth.ok_or(format!("Rule `{}´ has no `B´ part", rule))?;
+        let survival = survival.ok_or(format!("Rule `{}´ has no `S´ part", rule))?;
+
+        // Every index without active neighbours would become active, which never ends
+        if birth.contains(&0) {
+            return Err(String::from("Rules with `B0´ are not supported on an infinite field"));
         }
 
-        return *self.field.get(&index).unwrap()
+        Ok(Rule { birth, survival })
     }
 
-    fn set_field_value(&mut self, index: &IndexType, value: bool) {
-        let index = index.to_string();
-        self.field.insert(index, value);
+    /// Parses the counts of a rule part (`23´ or `2,3´)
+    fn parse_counts(counts: &str) -> Result<Vec<u32>, String> {
+        if counts.contains(',') {
+            counts.split(',')
+                .map(|count| count.trim().parse::<u32>()
+                    .map_err(|_| format!("Couldn't parse neighbour count `{}´", count)))
+                .collect()
+        } else {
+            counts.chars()
+                .map(|digit| digit.to_digit(10)
+                    .ok_or(format!("Couldn't parse neighbour count `{}´", digit)))
+                .collect()
+        }
+    }
+
+    /// Gets the next state of an index which is currently [active] and has [n_active_neighbours]
+    fn next_state(&self, active: bool, n_active_neighbours: u32) -> bool {
+        if active {
+            self.survival.contains(&n_active_neighbours)
+        } else {
+            self.birth.contains(&n_active_neighbours)
+        }
     }
+}
 
-    /// Returns (#active, #inactive) neighbours
-    fn get_neighbours_3d(&self, index: &IndexType) -> (u64, u64) {
-        let index_vec = &index.0;
+/// The rule of the puzzle: active fields stay active with 2 or 3 active neighbours,
+/// inactive fields become active with exactly 3 active neighbours
+impl Default for Rule {
+    fn default() -> Self {
+        Rule { birth: vec![3], survival: vec![2, 3] }
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let uses_commas = self.birth.iter().chain(self.survival.iter()).any(|count| *count > 9);
+        let separator = if uses_commas { "," } else { "" };
+        let join = |counts: &Vec<u32>| counts.iter().map(|c| c.to_string()).collect::<Vec<String>>().join(separator);
+
+        write!(f, "B{}/S{}", join(&self.birth), join(&self.survival))
+    }
+}
+
+/// How [export_cycles] writes the cycles
+pub enum ExportFormat {
+    /// One binary PGM image per cycle
+    Pgm,
+
+    /// One text file with all cycles after each other
+    TextLog
+}
 
-        let mut n_active = 0 as u64;
-        let mut n_inactive = 0 as u64;
-        for x in range(index_vec[0] - 1, index_vec[0] + 2) {
-            for y in range(index_vec[1] - 1, index_vec[1] + 2) {
-                for z in range(index_vec[2] - 1, index_vec[2] + 2) {
-                    let val = self.get_field_value(&IndexType(vec!(x, y, z)));
-                   if val {
-                       n_active += 1;
-                   } else {
-                       n_inactive += 1;
-                   };
+/// Gets a readable name of dimension [dim] (x, y, z, w, then d4, d5, ...)
+fn get_dimension_name(dim: usize) -> String {
+    match dim {
+        0 => String::from("x"),
+        1 => String::from("y"),
+        2 => String::from("z"),
+        3 => String::from("w"),
+        _ => format!("d{}", dim)
+    }
+}
+
+/// The smallest box containing a set of indices ([from] and [to] are both inclusive)
+#[derive(Debug, Clone)]
+struct BoundingBox<const N: usize> {
+    from: IndexType<N>,
+    to: IndexType<N>
+}
+
+impl<const N: usize> BoundingBox<N> {
+    /// Creates a box containing only [index]
+    fn new(index: &IndexType<N>) -> Self {
+        BoundingBox { from: *index, to: *index }
+    }
+
+    /// Grows [bounds] s.t. they contain [index] (creates them if there are none yet)
+    fn extend(bounds: &mut Option<BoundingBox<N>>, index: &IndexType<N>) {
+        match bounds {
+            Some(bounds) => {
+                for dim in 0..N {
+                    bounds.from[dim] = bounds.from[dim].min(index[dim]);
+                    bounds.to[dim] = bounds.to[dim].max(index[dim]);
                 }
-            }
+            },
+            None => *bounds = Some(BoundingBox::new(index))
         }
+    }
 
-        // Remove own field from the counts
-        if self.get_field_value(index) {
-            n_active -= 1;
-        } else {
-            n_inactive -= 1;
+    /// Checks if [index] touches the border of the box in any dimension
+    /// (i.e., the box might shrink if [index] is removed)
+    fn is_on_border(&self, index: &IndexType<N>) -> bool {
+        (0..N).any(|dim| index[dim] == self.from[dim] || index[dim] == self.to[dim])
+    }
+}
+
+/// Amount of dimensions covered by the start slice (x and y), all further dimensions
+/// start at exactly one coordinate (0)
+const SLICE_DIMS: usize = 2;
+
+/// Stores our current field
+/// as the set of active indices only (every index which is not contained is inactive)
+///
+/// Indices are stored packed into a single integer (see [pack]), s.t. moving to a
+/// neighbour is a single addition and hashing is cheap
+///
+/// Since the start slice is flat in all dimensions beyond [SLICE_DIMS], the field stays mirror
+/// symmetric around 0 in those dimensions forever. Those dimensions are also interchangeable,
+/// so swapping two of their coordinates doesn't change whether an index is active. If
+/// [use_symmetry] is set, only indices which are non-negative and ordered largest first in those
+/// dimensions are stored and every stored index stands for all of its mirror images and
+/// permutations
+pub struct Field<const N: usize> {
+    use_symmetry: bool,
+    active: PackedSet,
+    rule: Rule,
+    neighbourhood: Neighbourhood,
+
+    /// Bounds of the stored active indices ([None] if there are none), kept up to date
+    /// whenever indices change
+    bounds: Option<BoundingBox<N>>,
+
+    /// Offsets from an index to all of its neighbours as value to add onto a [PackedIndex]
+    /// (computed once since they never change)
+    neighbour_deltas: Vec<u64>,
+
+    /// Same offsets grouped by their part in the symmetric dimensions, each with the deltas of
+    /// their x and y part (only the symmetric part has to be mapped onto the stored index)
+    symmetric_offsets: Vec<(IndexType<N>, Vec<u64>)>,
+
+    /// How many indices got a neighbour count in the last [step] (to size the counts of the next one)
+    n_counted: usize
+}
+
+/// At most this many neighbour counts are allocated up front by a [step] (more grow as needed)
+const MAX_PREALLOCATED_COUNTS: usize = 1 << 20;
+
+
+/// All functions work on fields with any amount of dimensions
+impl<const N: usize> Field<N> {
+    fn new(use_symmetry: bool, rule: Rule, neighbourhood: Neighbourhood) -> Self {
+        assert!(N <= MAX_DIMS, "At most {} dimensions are supported", MAX_DIMS);
+        assert!(neighbourhood.get_radius() >= 1, "The neighbourhood needs a radius of at least 1");
+
+        let neighbour_offsets = neighbourhood.get_offsets::<N>();
+
+        // The bias cancels out when adding, so the plain sum of the shifted offsets remains
+        let to_delta = |offset: &IndexType<N>| offset.0.iter()
+            .enumerate()
+            .fold(0 as u64, |delta, (dim, v)| delta.wrapping_add((*v << (dim * bits_per_dim::<N>())) as u64));
+        let neighbour_deltas = neighbour_offsets.iter().map(to_delta).collect();
+
+        let mut symmetric_offsets: HashMap<IndexType<N>, Vec<u64>> = HashMap::new();
+        for offset in neighbour_offsets.iter() {
+            let (mut symmetric, mut slice) = (*offset, *offset);
+            for dim in 0..N {
+                if dim < SLICE_DIMS {
+                    symmetric[dim] = 0;
+                } else {
+                    slice[dim] = 0;
+                }
+            }
+            symmetric_offsets.entry(symmetric).or_insert_with(Vec::new).push(to_delta(&slice));
         }
 
-        return (n_active, n_inactive);
+        return Field {
+            use_symmetry,
+            active: PackedSet::default(),
+            rule,
+            neighbourhood,
+            bounds: None,
+            neighbour_deltas,
+            symmetric_offsets: symmetric_offsets.into_iter().collect(),
+            n_counted: 0
+        }
     }
 
-    /// Returns (#active, #inactive) neighbours
-    fn get_neighbours_4d(&self, index: &IndexType) -> (u64, u64) {
-        let index_vec = &index.0;
+    fn get_field_value(&self, index: &IndexType<N>) -> bool {
+        return self.active.contains(&pack(&self.to_stored_index(index)));
+    }
 
-        let mut n_active = 0 as u64;
-        let mut n_inactive = 0 as u64;
-        for x in range(index_vec[0] - 1, index_vec[0] + 2) {
-            for y in range(index_vec[1] - 1, index_vec[1] + 2) {
-                for z in range(index_vec[2] - 1, index_vec[2] + 2) {
-                    for d in range(index_vec[3] - 1, index_vec[3] + 2) {
-                        let val = self.get_field_value(&IndexType(vec!(x, y, z, d)));
-                        if val {
-                            n_active += 1;
-                        } else {
-                            n_inactive += 1;
-                        };
-                    }
+    fn set_field_value(&mut self, index: &IndexType<N>, value: bool) {
+        let index = self.to_stored_index(index);
+        if value {
+            self.active.insert(pack(&index));
+            BoundingBox::extend(&mut self.bounds, &index);
+        } else if self.active.remove(&pack(&index)) {
+            // Only removing an index from the border can shrink the bounds
+            if self.bounds.as_ref().map_or(false, |bounds| bounds.is_on_border(&index)) {
+                self.bounds = None;
+                for stored in self.active.iter() {
+                    BoundingBox::extend(&mut self.bounds, &unpack(*stored));
                 }
             }
         }
+    }
 
-        // Remove own field from the counts
-        if self.get_field_value(index) {
-            n_active -= 1;
-        } else {
-            n_inactive -= 1;
+    /// Maps [index] onto the index which represents it within [active] (i.e., mirrors it into
+    /// the non-negative half and sorts the symmetric dimensions if we use the symmetry)
+    fn to_stored_index(&self, index: &IndexType<N>) -> IndexType<N> {
+        let mut stored = *index;
+        if self.use_symmetry {
+            let symmetric = &mut stored.0[SLICE_DIMS..];
+            for value in symmetric.iter_mut() {
+                *value = value.abs();
+            }
+            symmetric.sort_unstable_by(|a, b| b.cmp(a));
+        }
+
+        stored
+    }
+
+    /// How many indices of the full field are represented by the stored [index]
+    ///
+    /// Each symmetric dimension which is not 0 doubles the amount of mirror images and the
+    /// coordinates of the symmetric dimensions can be ordered in `n! / (k1! * k2! * ...)´ ways
+    /// (with n symmetric dimensions and k1, k2, ... times the same coordinate)
+    fn get_weight(&self, index: PackedIndex) -> u64 {
+        if !self.use_symmetry {
+            return 1;
+        }
+
+        let values: IndexType<N> = unpack(index);
+        let mut weight: u64 = 1;
+        let mut n_same = 0;
+        for dim in SLICE_DIMS..N {
+            if values[dim] != 0 {
+                weight *= 2;
+            }
+
+            // equal coordinates are next to each other since they are sorted, each new one
+            // extends the multinomial coefficient (always divisible, since it stays an integer)
+            n_same = if dim > SLICE_DIMS && values[dim] == values[dim - 1] { n_same + 1 } else { 1 };
+            weight = weight * (dim - SLICE_DIMS + 1) as u64 / n_same;
+        }
+
+        weight
+    }
+
+    /// Renders every slice of the current extends as text
+    ///
+    /// Each slice (one for each combination of coordinates beyond x and y) gets a title with its
+    /// coordinates followed by a row of x labels (last digit of the coordinate) and one line per y
+    fn render_slices(&self) -> String {
+        let (from, to) = match self.get_extends() {
+            Some(extends) => extends,
+            None => return String::from("(no active cubes)\n")
+        };
+        let mut rendered = String::new();
+
+        for slice in self.get_slices(&from, &to) {
+            let grid = self.get_slice_grid(&slice, &from, &to);
+            let title: Vec<String> = (SLICE_DIMS..N)
+                .map(|dim| format!("{}={}", get_dimension_name(dim), slice[dim]))
+                .collect();
+            rendered += &format!("{} (x={}..{}, y={}..{})\n",
+                                 if title.is_empty() { String::from("slice") } else { title.join(", ") },
+                                 from[0], to[0], from[1], to[1]);
+
+            // x labels, only the last digit fits above each column
+            rendered += "     ";
+            for x in range(from[0], to[0] + 1) {
+                rendered += &(x.abs() % 10).to_string();
+            }
+            rendered += "\n";
+
+            let rows = grid.map(|active| if *active { '#' } else { '.' }).to_string();
+            for (y, row) in range(from[1], to[1] + 1).zip(rows.lines()) {
+                rendered += &format!("{:>4} {}\n", y, row);
+            }
+            rendered += "\n";
         }
 
-        return (n_active, n_inactive);
+        rendered
     }
 
-    /// Small helper function to print a part of the field
-    fn print_field_3d(&self) {
-        for z in range(0, 3) {
-            for y in range(-5, 5) {
-                for x in range(-5, 5) {
-                    let val = self.get_field_value(&IndexType(vec![x as i64, y as i64, z as i64]));
-                    if val {
-                        print!("#")
-                    } else {
-                        print!(".")
+    /// Renders every slice of the current extends as binary PGM image
+    ///
+    /// Slices are tiled next to each other: the last dimension goes from left to right, all other
+    /// dimensions beyond x and y from top to bottom. Each cube gets [scale] x [scale] pixels
+    /// (white if active) and the slices are separated by a grey line
+    fn render_pgm(&self, scale: usize) -> Vec<u8> {
+        let (from, to) = match self.get_extends() {
+            Some(extends) => extends,
+            None => return b"P5\n1 1\n255\n\0".to_vec()
+        };
+        let slices = self.get_slices(&from, &to);
+
+        let slice_width = (to[0] - from[0] + 1) as usize * scale;
+        let slice_height = (to[1] - from[1] + 1) as usize * scale;
+        let n_columns = if N > SLICE_DIMS {
+            (to[N - 1] - from[N - 1] + 1) as usize
+        } else {
+            1
+        };
+        let n_rows = slices.len() / n_columns;
+
+        let width = n_columns * (slice_width + 1) - 1;
+        let height = n_rows * (slice_height + 1) - 1;
+        let mut pixels = vec![128 as u8; width * height];
+
+        for (i, slice) in slices.iter().enumerate() {
+            let left = (i % n_columns) * (slice_width + 1);
+            let top = (i / n_columns) * (slice_height + 1);
+            let grid = self.get_slice_grid(slice, &from, &to);
+
+            for (x, y) in grid.positions() {
+                let value = if *grid.get(x, y).unwrap() { 255 } else { 0 };
+                for pixel_y in top + y * scale..top + (y + 1) * scale {
+                    for pixel_x in left + x * scale..left + (x + 1) * scale {
+                        pixels[pixel_y * width + pixel_x] = value;
                     }
                 }
-                println!();
             }
-            println!();
         }
+
+        let mut image = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+        image.extend(pixels);
+        image
     }
 
-    /// Will get the amount of dimension (3d or 4d here)
-    ///
-    /// Its done by counting the amount of fields in one of the keys of the [field]
+    /// Gets the x-y-plane at [slice] (the coordinates beyond x and y) within [from] and [to]
+    fn get_slice_grid(&self, slice: &IndexType<N>, from: &IndexType<N>, to: &IndexType<N>) -> Grid<bool> {
+        let width = (to[0] - from[0] + 1) as usize;
+        let height = (to[1] - from[1] + 1) as usize;
+        let mut grid = Grid::new(width, height, false);
+
+        for (x, y) in grid.positions().collect::<Vec<(usize, usize)>>() {
+            let mut index = *slice;
+            index[0] = from[0] + x as i64;
+            index[1] = from[1] + y as i64;
+            grid.set(x, y, self.get_field_value(&index));
+        }
+
+        grid
+    }
+
+    /// Gets the coordinates of all slices within [from] and [to]
+    /// (i.e., the dimensions beyond x and y, x and y are always 0)
+    fn get_slices(&self, from: &IndexType<N>, to: &IndexType<N>) -> Vec<IndexType<N>> {
+        let (mut from, mut to) = (*from, *to);
+        for dim in 0..SLICE_DIMS {
+            from[dim] = 0;
+            to[dim] = 0;
+        }
+
+        PointsInBox::new(from, to).collect()
+    }
+
+    /// Will get the amount of dimensions
     fn get_dimensions(&self) -> usize {
-        if self.field.is_empty() { return 0; }
-        (IndexType::from(self.field.keys().next().unwrap().clone()).0.len())
+        N
+    }
+
+    /// Gets the current minima and maxima of the active indices for each dimension
+    /// (both inclusive) or [None] if no index is active
+    fn get_extends(&self) -> Option<(IndexType<N>, IndexType<N>)> {
+        let bounds = self.bounds.as_ref()?;
+        let mut from = bounds.from;
+
+        let mut to = bounds.to;
+
+        // the mirror images and permutations of the stored indices reach as far as the largest
+        // stored coordinate (which is always in the first symmetric dimension) in every
+        // symmetric dimension and into both directions
+        if self.use_symmetry && self.get_dimensions() > SLICE_DIMS {
+            let largest = bounds.to[SLICE_DIMS];
+            for i in SLICE_DIMS..self.get_dimensions() {
+                from[i] = -largest;
+                to[i] = largest;
+            }
+        }
+
+        return Some((from, to));
+    }
+
+    /// Gets the deltas from a stored index with the [symmetric] part (x and y are 0) to the stored
+    /// indices of its neighbours, together with how many offsets lead to each of them
+    fn get_symmetric_deltas(&self, symmetric: &IndexType<N>) -> Vec<(u64, u64)> {
+        let origin = pack(symmetric);
+        let mut deltas: PackedMap<u64> = PackedMap::default();
+
+        for (symmetric_offset, slice_deltas) in self.symmetric_offsets.iter() {
+            let stored = pack(&self.to_stored_index(&(*symmetric + *symmetric_offset)));
+            for slice_delta in slice_deltas.iter() {
+                *deltas.entry(stored.wrapping_sub(origin).wrapping_add(*slice_delta)).or_insert(0) += 1;
+            }
+        }
+
+        deltas.into_iter().collect()
     }
 
-    /// Gets the current minima and maxima for each dimension
+    /// Does one cycle of the game on all dimensions
     ///
-    /// If you want to save time, you could just remember them when creating / removing outer stars
-    fn get_extends(&self) -> (IndexType, IndexType) {
-        let n_dims = self.get_dimensions();
-        let mut from = vec![i64::max_value(); n_dims];
-        let mut to = vec![i64::min_value(); n_dims];
-
-        for point_str in self.field.keys().into_iter() {
-            for (i, value) in (IndexType::from(point_str.clone())).0.iter().enumerate() {
-                if from[i] > *value {
-                    from[i] = *value;
-                } else if to[i] < *value{
-                    to[i] = *value;
-                }
+    /// Instead of looking at every index of the bounding box, each active index adds one to the
+    /// neighbour count of all its neighbours. Only indices which got counted (and the active ones)
+    /// can be active afterwards
+    ///
+    /// fails (and leaves the field as it is) if the neighbours wouldn't fit into a [PackedIndex]
+    fn step(&mut self) -> Result<(), String> {
+        // Make sure the neighbours still fit into the packed representation
+        let radius = self.neighbourhood.get_radius();
+        if let Some((from, to)) = self.get_extends() {
+            if from.0.iter().any(|v| *v - radius < min_coordinate::<N>()) || to.0.iter().any(|v| *v + radius > max_coordinate::<N>()) {
+                return Err(format!("Field grew beyond the supported coordinates ({}..={})",
+                                   min_coordinate::<N>(), max_coordinate::<N>()));
             }
         }
 
-        return (IndexType(from), IndexType(to));
-    }
-
-    fn step3d(&mut self) {
-        let mut new_field = HashMap::new();
-        let (from, to) = self.get_extends();
-        for x in range(from.0.get(0).unwrap() - 1, to.0.get(0).unwrap() + 2) {
-            for y in range(from.0.get(1).unwrap() - 1, to.0.get(1).unwrap() + 2) {
-                for z in range(from.0.get(2).unwrap() - 1, to.0.get(2).unwrap() + 2) {
-                    let val = self.get_field_value(&IndexType(vec!(x,y,z)));
-                    let index = IndexType(vec![x,y,z]);
-                    let (active, _) = self.get_neighbours_3d(&index);
-
-                    let mut new_val = true;
-                    if val {
-                        if !(active == 2 || active == 3) {
-                            new_val = false;
-                        }
-                    } else {
-                        if !(active == 3) {
-                            new_val = false;
-                        }
-                    }
+        // Most neighbours are shared, so the last step tells much better than the amount of
+        // neighbours how many indices get counted
+        let mut n_active_neighbours: PackedMap<u64> = PackedMap::with_capacity_and_hasher(
+            self.n_counted.max(self.active.len()).min(MAX_PREALLOCATED_COUNTS), Default::default());
 
-                    if new_val {
-                        new_field.insert(index.to_string(), new_val);
-                    }
+        // active indices without any active neighbour have to be judged also
+        for index in self.active.iter() {
+            n_active_neighbours.insert(*index, 0);
+        }
+
+        // Indices with the same symmetric part reach the same stored neighbours (relative to them),
+        // so those are only computed once per symmetric part
+        let mut symmetric_deltas: PackedMap<Vec<(u64, u64)>> = PackedMap::default();
+
+        for index in self.active.iter() {
+            if !self.use_symmetry {
+                for delta in self.neighbour_deltas.iter() {
+                    *n_active_neighbours.entry(index.wrapping_add(*delta)).or_insert(0) += 1;
                 }
+                continue;
+            }
+
+            // With symmetry [index] stands for the class A of all its images and each neighbour
+            // for its class R. Counting the touching pairs of both classes from either side gives
+            // `|A| * (offsets from index into R) = |R| * (neighbours of one index of R in A)´,
+            // so each offset adds |A| here and the sum is divided by |R| below
+            let mut symmetric: IndexType<N> = unpack(*index);
+            for dim in 0..SLICE_DIMS {
+                symmetric[dim] = 0;
+            }
+            let weight = self.get_weight(*index);
+            let deltas = symmetric_deltas
+                .entry(pack(&symmetric))
+                .or_insert_with(|| self.get_symmetric_deltas(&symmetric));
+            for (delta, n_offsets) in deltas.iter() {
+                *n_active_neighbours.entry(index.wrapping_add(*delta)).or_insert(0) += weight * n_offsets;
             }
         }
-        self.field = new_field;
-    }
-
-    fn step4d(&mut self) {
-        let mut new_field = HashMap::new();
-        let (from, to) = self.get_extends();
-        for x in range(from.0.get(0).unwrap() - 1, to.0.get(0).unwrap() + 2) {
-            for y in range(from.0.get(1).unwrap() - 1, to.0.get(1).unwrap() + 2) {
-                for z in range(from.0.get(2).unwrap() - 1, to.0.get(2).unwrap() + 2) {
-                    for d in range(from.0.get(3).unwrap() - 1, to.0.get(3).unwrap() + 2) {
-                        let index = IndexType(vec![x, y, z, d]);
-                        let val = self.get_field_value(&index);
-                        let (active, _) = self.get_neighbours_4d(&index);
-                        let mut new_val = true;
-
-                        if val {
-                            if !(active == 2 || active == 3) {
-                                new_val = false;
-                            }
-                        } else {
-                            if !(active == 3) {
-                                new_val = false;
-                            }
-                        }
-                        if new_val {
-                            new_field.insert(index.to_string(), new_val);
-                        }
-                    }
-                }
+
+        self.n_counted = n_active_neighbours.len();
+
+        // Collect the new active indices and their bounds at once
+        let mut new_active = PackedSet::default();
+        let mut new_bounds: Option<BoundingBox<N>> = None;
+        for (index, count) in n_active_neighbours.into_iter() {
+            let active = (count / self.get_weight(index)) as u32;
+            if self.rule.next_state(self.active.contains(&index), active) {
+                new_active.insert(index);
+                BoundingBox::extend(&mut new_bounds, &unpack(index));
             }
         }
-        self.field = new_field;
+
+        self.active = new_active;
+        self.bounds = new_bounds;
+        Ok(())
     }
 
     pub fn count_active_blocks(&self) -> u64 {
-        self.field.values().filter(|f| **f).map(|b| {
-            if *b {
-                return 1 as u64;
-            }
-            0 as u64
-        }).sum::<u64>()
+        self.active.iter().map(|index| self.get_weight(*index)).sum::<u64>()
     }
 
 }
 
 pub fn task1() {
-    let input = get_input_data();
-    let mut field = parse_start_field(input, false);
-    field.step3d();
-    // field.print_field_3d();
-    field.step3d();
-    field.step3d();
-    field.step3d();
-    field.step3d();
-    field.step3d();
-
-    let sum_of_active = field.count_active_blocks();
+    let sum_of_active = run(get_input_data(), 3, 6).expect("Couldn't run the game");
     println!("Sum of actives (Task 1) {}", sum_of_active);
 }
 
 pub fn task2() {
-    let input = get_input_data();
-    let mut field = parse_start_field(input, true);
-    field.step4d();
-    field.step4d();
-    field.step4d();
-    field.step4d();
-    field.step4d();
-    field.step4d();
+    let sum_of_active = run(get_input_data(), 4, 6).expect("Couldn't run the game");
+    println!("Sum of actives (Task 2) {}", sum_of_active);
+}
+
+/// Same as [task1] and [task2] but with an arbitrary amount of dimensions (2d, 5d, 6d, ...)
+pub fn task_with_dimensions(n_dims: usize) {
+    let sum_of_active = run(get_input_data(), n_dims, 6).expect("Couldn't run the game");
+    println!("Sum of actives ({}d) {}", n_dims, sum_of_active);
+}
 
-    let sum_of_active = field.count_active_blocks();
+/// Same as [task_with_dimensions] but with another rule (like `B36/S23´) and neighbourhood
+pub fn task_with_rule(n_dims: usize, rule: &str, neighbourhood: Neighbourhood) {
+    let rule = Rule::parse(rule).expect("Invalid rule");
+    println!("Sum of actives ({}d, {}, {:?}) {}",
+             n_dims,
+             rule,
+             neighbourhood,
+             run_with_rule(get_input_data(), n_dims, 6, rule.clone(), neighbourhood).expect("Couldn't run the game"));
+}
 
-    println!("Sum of actives (Task 2) {}", sum_of_active);
+/// Measures the six cycles of the puzzle in every supported amount of dimensions
+///
+/// with the symmetry reduction each of them should take at most a few seconds (in a release build)
+pub fn task_benchmark() {
+    for n_dims in SLICE_DIMS..=MAX_DIMS {
+        let start = std::time::Instant::now();
+        let sum_of_active = run(get_input_data(), n_dims, 6).expect("Couldn't run the game");
+        println!("Sum of actives ({}d) {} in {:.3} s", n_dims, sum_of_active, start.elapsed().as_secs_f64());
+    }
+}
 
+/// Prints every slice of the field after each of the six cycles
+pub fn task_print(n_dims: usize) {
+    for_dimensions!(n_dims, print_cycles(get_input_data(), 6)).expect("Couldn't play the game");
 }
 
-/// Reads the first layer of data
+/// Prints every slice of the field (in [N] dimensions) after each of the [n_cycles]
+fn print_cycles<const N: usize>(input: &str, n_cycles: usize) -> Result<(), String> {
+    let mut field = parse_start_field::<N>(input, true, Rule::default(), Neighbourhood::Moore(1))?;
+    for cycle in 0..=n_cycles {
+        if cycle > 0 {
+            field.step()?;
+        }
+        println!("After {} cycles:\n", cycle);
+        print!("{}", field.render_slices());
+    }
+
+    Ok(())
+}
+
+/// Writes all six cycles of the field into [directory] in [format]
+pub fn task_export(n_dims: usize, directory: &str, format: ExportFormat) {
+    export_cycles(get_input_data(), n_dims, 6, directory, format)
+        .expect("Couldn't export the cycles");
+    println!("Exported cycles to {}", directory);
+}
+
+/// Plays the game like [run] and writes the start field and the field after each cycle
 ///
-/// the game can be started in 3d or 4d mode the read input will represent
-/// exactly one slice of the dimensions
-fn parse_start_field(input: &str, generate_4d_field: bool) -> Field {
-    let mut field = Field::new();
-
-    for (col, line) in input.split("\r\n").enumerate() {
-        for (row, value) in line.chars().into_iter().enumerate() {
-            let val: bool = match value {
-                '.' => false,
-                '#' => true,
-                _ => panic!("Invalid char found")
-            };
+/// [ExportFormat::Pgm] creates one image per cycle (`cycle_00.pgm´, `cycle_01.pgm´, ...),
+/// [ExportFormat::TextLog] creates one `cycles.txt´ containing the text rendering of all cycles
+///
+/// fails with [std::io::ErrorKind::InvalidInput] if [n_dims] is not supported
+pub fn export_cycles(input: &str, n_dims: usize, n_cycles: usize, directory: &str, format: ExportFormat) -> std::io::Result<()> {
+    for_dimensions!(n_dims, export_field_cycles(input, n_cycles, directory, format))
+}
 
-            if !generate_4d_field {
-                // 3d field
-                field.set_field_value(&IndexType(vec![row as i64, col as i64, 1 as i64]), val);
-            } else{
-                // 4d field
-                field.set_field_value(&IndexType(vec![row as i64, col as i64, 1 as i64, 1 as i64]), val);
+/// [export_cycles] for a field with [N] dimensions
+fn export_field_cycles<const N: usize>(input: &str, n_cycles: usize, directory: &str, format: ExportFormat) -> std::io::Result<()> {
+    let mut field = parse_start_field::<N>(input, true, Rule::default(), Neighbourhood::Moore(1))
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    let directory = Path::new(directory);
+    fs::create_dir_all(directory)?;
+
+    let mut log = match format {
+        ExportFormat::TextLog => Some(File::create(directory.join("cycles.txt"))?),
+        ExportFormat::Pgm => None
+    };
+
+    for cycle in 0..=n_cycles {
+        if cycle > 0 {
+            field.step().map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        }
 
+        match log.as_mut() {
+            Some(log) => {
+                writeln!(log, "=== Cycle {} ({} active) ===\n", cycle, field.count_active_blocks())?;
+                write!(log, "{}", field.render_slices())?;
+            },
+            None => {
+                let image = field.render_pgm(4);
+                fs::write(directory.join(format!("cycle_{:02}.pgm", cycle)), image)?;
             }
         }
     }
 
-    field
+    Ok(())
+}
+
+/// Plays the game for [n_cycles] in [n_dims] dimensions starting from the slice in [input]
+///
+/// returns the amount of active blocks afterwards, fails if [n_dims] is not supported, [input] is no
+/// valid slice or the field grows beyond the coordinates a [PackedIndex] can hold
+pub fn run(input: &str, n_dims: usize, n_cycles: usize) -> Result<u64, String> {
+    run_with_rule(input, n_dims, n_cycles, Rule::default(), Neighbourhood::Moore(1))
+}
+
+/// Same as [run] but with any life-like [rule] and [neighbourhood]
+pub fn run_with_rule(input: &str, n_dims: usize, n_cycles: usize, rule: Rule, neighbourhood: Neighbourhood) -> Result<u64, String> {
+    for_dimensions!(n_dims, run_field(input, n_cycles, true, rule, neighbourhood))
 }
 
+/// Same as [run] but stores the full field (i.e., does not make use of the symmetry)
+pub fn run_without_symmetry(input: &str, n_dims: usize, n_cycles: usize) -> Result<u64, String> {
+    for_dimensions!(n_dims, run_field(input, n_cycles, false, Rule::default(), Neighbourhood::Moore(1)))
+}
+
+/// Plays the game in [N] dimensions (see [run_with_rule])
+fn run_field<const N: usize>(input: &str, n_cycles: usize, use_symmetry: bool, rule: Rule, neighbourhood: Neighbourhood) -> Result<u64, String> {
+    let mut field = parse_start_field::<N>(input, use_symmetry, rule, neighbourhood)?;
+    for _ in 0..n_cycles {
+        field.step()?;
+    }
+
+    Ok(field.count_active_blocks())
+}
+
+/// Reads the first layer of data
+///
+/// the game can be started with any amount of dimensions (at least 2). The read input will represent
+/// exactly one slice of the dimensions, all other dimensions of the slice are 0
+///
+/// fails if the slice is not rectangular or doesn't fit into a [PackedIndex] with room for the
+/// neighbours of at least one cycle (or the field can't be created with [N] dimensions and [neighbourhood])
+pub fn parse_start_field<const N: usize>(input: &str, use_symmetry: bool, rule: Rule, neighbourhood: Neighbourhood) -> Result<Field<N>, String> {
+    if N < SLICE_DIMS || N > MAX_DIMS {
+        return Err(format!("{} dimensions are not supported (only {} to {})", N, SLICE_DIMS, MAX_DIMS));
+    }
+    if neighbourhood.get_radius() < 1 {
+        return Err(String::from("The neighbourhood needs a radius of at least 1"));
+    }
+
+    let grid = Grid::parse(input, |value| match value {
+        '.' => Some(false),
+        '#' => Some(true),
+        _ => None
+    })?;
+
+    // The slice starts at 0 and the neighbours of its border have to fit as well
+    let max_size = (max_coordinate::<N>() + 1 - neighbourhood.get_radius()).max(0) as usize;
+    if grid.get_width() > max_size || grid.get_height() > max_size {
+        return Err(format!("The start slice is {}x{} but may be at most {}x{} in {} dimensions with a radius of {}",
+                           grid.get_width(), grid.get_height(), max_size, max_size, N, neighbourhood.get_radius()));
+    }
+
+    let mut field = Field::new(use_symmetry, rule, neighbourhood);
+
+    for (x, y) in grid.positions() {
+        let mut index = Point::origin();
+        index[0] = x as i64;
+        index[1] = y as i64;
+        field.set_field_value(&index, *grid.get(x, y).unwrap());
+    }
+
+    Ok(field)
+}
+
+
+/// Generates a start slice of [size] x [size] fields (at least one) from [seed]
+///
+/// The slice is at most 122 fields wide, s.t. it still fits into a [PackedIndex] after six cycles
+/// in any amount of dimensions
+pub fn generate_input(size: usize, seed: u64) -> String {
+    let mut random = Random::new(seed);
+    let size = size.max(1).min((max_coordinate::<MAX_DIMS>() + 1) as usize - 6);
+
+    let mut input = String::new();
+    for _ in 0..size {
+        let row: String = (0..size).map(|_| if random.one_in(2) { '#' } else { '.' }).collect();
+        input.push_str(&row);
+        input.push('\n');
+    }
+
+    input
+}
 
 /// Reads the file into the binary
 fn get_input_data() -> &'static str {
     return include_str!("input.txt");
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_dimensions_are_errors() {
+        let input = ".#.\n..#\n###\n";
+        assert_eq!(run(input, 1, 1), Err(String::from("1 dimensions are not supported (only 2 to 8)")));
+        assert_eq!(run(input, 9, 1), Err(String::from("9 dimensions are not supported (only 2 to 8)")));
+        assert!(run_without_symmetry(input, 0, 1).is_err());
+
+        let error = export_cycles(input, 9, 1, "unused", ExportFormat::TextLog).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(!Path::new("unused").exists());
+    }
+
+    /// The bounds of all stored indices, computed from scratch
+    fn recompute_bounds<const N: usize>(field: &Field<N>) -> Option<(IndexType<N>, IndexType<N>)> {
+        let mut bounds = None;
+        for stored in field.active.iter() {
+            BoundingBox::extend(&mut bounds, &unpack::<N>(*stored));
+        }
+        bounds.map(|bounds| (bounds.from, bounds.to))
+    }
+
+    fn tracked_bounds<const N: usize>(field: &Field<N>) -> Option<(IndexType<N>, IndexType<N>)> {
+        field.bounds.as_ref().map(|bounds| (bounds.from, bounds.to))
+    }
+
+    fn check_tracked_bounds<const N: usize>(input: &str, use_symmetry: bool) {
+        let mut field = parse_start_field::<N>(input, use_symmetry, Rule::default(), Neighbourhood::Moore(1)).unwrap();
+        assert_eq!(tracked_bounds(&field), recompute_bounds(&field));
+        for _ in 0..4 {
+            field.step().unwrap();
+            assert_eq!(tracked_bounds(&field), recompute_bounds(&field));
+        }
+
+        // Remove the indices one by one, border ones first, s.t. the box has to shrink
+        let mut stored: Vec<IndexType<N>> = field.active.iter().map(|index| unpack(*index)).collect();
+        stored.sort_by_key(|index| std::cmp::Reverse(index.0.iter().map(|v| v.abs()).sum::<i64>()));
+        for index in stored.iter() {
+            field.set_field_value(index, false);
+            assert_eq!(tracked_bounds(&field), recompute_bounds(&field));
+        }
+        assert!(tracked_bounds(&field).is_none());
+    }
+
+    #[test]
+    fn tracked_bounds_match_the_active_indices() {
+        // The glider of the puzzle, a blinker and a single block which dies out in the first cycle
+        for input in [".#.\n..#\n###\n", "...\n###\n...\n", "#\n"].iter() {
+            for use_symmetry in [true, false].iter() {
+                check_tracked_bounds::<2>(input, *use_symmetry);
+                check_tracked_bounds::<3>(input, *use_symmetry);
+                check_tracked_bounds::<4>(input, *use_symmetry);
+            }
+        }
+    }
+
+    /// Only low dimensions and few cycles are checked since the full field gets slow quickly
+    #[test]
+    fn symmetry_does_not_change_the_result() {
+        let inputs = [String::from(".#.\n..#\n###\n"), String::from("#\n"), generate_input(5, 1), generate_input(6, 2)];
+        for input in inputs.iter() {
+            for n_dims in 2..=5 {
+                for n_cycles in 0..=3 {
+                    assert_eq!(run(input, n_dims, n_cycles), run_without_symmetry(input, n_dims, n_cycles),
+                               "Symmetry reduction differs for {}d after {} cycles on\n{}", n_dims, n_cycles, input);
+                }
+            }
+        }
+    }
+
+    /// Runs [input] for [n_cycles] in [N] dimensions with and without the symmetry
+    fn check_symmetry<const N: usize>(input: &str, n_cycles: usize, rule: &Rule, neighbourhood: Neighbourhood) {
+        let with_symmetry = run_field::<N>(input, n_cycles, true, rule.clone(), neighbourhood);
+        let without_symmetry = run_field::<N>(input, n_cycles, false, rule.clone(), neighbourhood);
+        assert_eq!(with_symmetry, without_symmetry, "Symmetry reduction differs for {}d, {}, {:?} after {} cycles on\n{}",
+                   N, rule, neighbourhood, n_cycles, input);
+    }
+
+    #[test]
+    fn symmetry_does_not_change_the_result_of_other_rules() {
+        let inputs = [String::from(".#.\n..#\n###\n"), generate_input(4, 3)];
+        for rule in ["B3/S23", "B36/S23", "B1/S1", "B2/S34", "B13/S012", "B5,6,7/S4,5,6,7,8,9"].iter() {
+            let rule = Rule::parse(rule).unwrap();
+            for input in inputs.iter() {
+                for &neighbourhood in [Neighbourhood::Moore(1), Neighbourhood::VonNeumann(1)].iter() {
+                    check_symmetry::<3>(input, 3, &rule, neighbourhood);
+                    check_symmetry::<4>(input, 3, &rule, neighbourhood);
+                    check_symmetry::<5>(input, 2, &rule, neighbourhood);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn symmetry_does_not_change_the_result_of_larger_neighbourhoods() {
+        let input = ".#.\n..#\n###\n";
+        for rule in ["B3/S23", "B4,5/S3,4,5,6", "B2/S"].iter() {
+            let rule = Rule::parse(rule).unwrap();
+            for &neighbourhood in [Neighbourhood::Moore(2), Neighbourhood::VonNeumann(2), Neighbourhood::VonNeumann(3)].iter() {
+                check_symmetry::<3>(input, 2, &rule, neighbourhood);
+                check_symmetry::<4>(input, 2, &rule, neighbourhood);
+            }
+        }
+    }
+
+    /// The puzzle in every supported amount of dimensions (the higher ones only work in
+    /// reasonable time and memory with the symmetry)
+    #[test]
+    fn puzzle_runs_in_all_dimensions() {
+        let expected = [27, 276, 2136, 10508, 64736, 360320, 1829760];
+        for (n_dims, expected) in (SLICE_DIMS..=MAX_DIMS).zip(expected.iter()) {
+            assert_eq!(run(get_input_data(), n_dims, 6), Ok(*expected), "Wrong result in {}d", n_dims);
+        }
+    }
+
+    /// Generated slices run for six cycles in the most dimensions (the largest one only for a
+    /// single cycle, since it takes long)
+    #[test]
+    fn generated_slices_run_in_the_most_dimensions() {
+        for &(size, seed) in [(0, 1), (8, 1)].iter() {
+            let input = generate_input(size, seed);
+            assert!(run(&input, MAX_DIMS, 6).is_ok(), "Couldn't run the generated slice of size {} (seed {})", size, seed);
+        }
+
+        let largest = generate_input(1000, 4);
+        assert_eq!(largest.lines().count(), 122);
+        assert!(run(&largest, MAX_DIMS, 1).is_ok());
+    }
+
+    #[test]
+    fn start_slice_leaves_room_for_one_cycle() {
+        let row_127 = format!("{}\n", "#".repeat(127));
+        let row_128 = format!("{}\n", "#".repeat(128));
+        assert!(run(&row_127, 8, 1).is_ok());
+        assert_eq!(run(&row_128, 8, 1),
+                   Err(String::from("The start slice is 128x1 but may be at most 127x127 in 8 dimensions with a radius of 1")));
+        assert!(run(&row_128.repeat(128), 3, 1).is_ok());
+        assert!(run_with_rule(&row_127, 8, 1, Rule::default(), Neighbourhood::Moore(2)).is_err());
+        assert!(run_with_rule("#\n", 2, 1, Rule::default(), Neighbourhood::Moore(200)).is_ok());
+    }
+}
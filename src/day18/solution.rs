@@ -1,17 +1,23 @@
-use crate::files::str_to_lines;
+use crate::files::read_file_to_lines;
+use crate::random::Random;
 use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, IsTerminal, Write};
+use num::{BigInt, Integer, One, Signed, ToPrimitive, Zero};
 
 pub fn task1(with_operator_precedence: bool) {
     let data = get_input_data();
-    let lines = str_to_lines(data);
 
-    let results: Vec<i64> = lines
-        .into_iter()
-        .map(|line| compute_formula(&line, with_operator_precedence))
-        .collect();
+    let results: Vec<i64> = compute_lines::<i64>(data, &get_operator_table(with_operator_precedence))
+        .unwrap_or_else(|report| panic!("Couldn't compute the formulas:\n{}", report));
 
     println!("Results: {:?} (with precendece={:?})", results, with_operator_precedence);
-    println!("Sum of results {} (with precendece={:?})", results.into_iter().sum::<i64>(), with_operator_precedence);
+    let sum = results
+        .into_iter()
+        .try_fold(0 as i64, |sum, result| sum.checked_add(result))
+        .expect("Sum of results overflows, try `task_big_integers´");
+    println!("Sum of results {} (with precendece={:?})", sum, with_operator_precedence);
 }
 
 
@@ -19,18 +25,107 @@ pub fn task2() {
     task1(true);
 }
 
-/// Inicates what we are doing right now
-enum State {
-    ReadsLeftOperand,
-    ReadsRightOperand,
-    ReadsOperator
+/// Same as [task1] but computes with arbitrary precision, s.t. nothing can overflow
+pub fn task_big_integers(with_operator_precedence: bool) {
+    let data = get_input_data();
+
+    let sum = compute_lines::<BigInt>(data, &get_operator_table(with_operator_precedence))
+        .unwrap_or_else(|report| panic!("Couldn't compute the formulas:\n{}", report))
+        .into_iter()
+        .fold(BigInt::zero(), |sum, result| sum + result);
+
+    println!("Sum of results {} (with precendece={:?}, big integers)", sum, with_operator_precedence);
 }
 
-/// computes the equation
+/// Computes each line of the file [filename] (like a sheet of homework)
 ///
-/// if [with_operator_precedence] is [true] we will execute + before *
-/// otherwise all operators are treated equal
-pub fn compute_formula(formula: &str, with_operator_precedence: bool) -> i64 {
+/// prints either all results or every error found in the file
+pub fn task_file(filename: &str, with_operator_precedence: bool) {
+    let data = read_file_to_lines(filename).join("\n");
+
+    match compute_lines::<BigInt>(&data, &get_operator_table(with_operator_precedence)) {
+        Ok(results) => {
+            for result in results {
+                println!("{}", result);
+            }
+        },
+        Err(report) => println!("{}", report)
+    }
+}
+
+/// Measures parsing and evaluating generated formulas of growing size (up to 1 MB)
+/// and compares it to the first solution of the task (see [compute_formula_original])
+///
+/// the time per byte should stay about the same for every size. The first solution gets slower
+/// per byte and takes about a minute for all sizes together
+pub fn task_benchmark() {
+    for &size in [1 << 10, 1 << 14, 1 << 17, 1 << 20].iter() {
+        let formula = generate_formula(size, size as u64);
+
+        let mut results: Vec<i64> = Vec::new();
+        for &with_operator_precedence in [false, true].iter() {
+            let operators = get_operator_table(with_operator_precedence);
+            let start = std::time::Instant::now();
+            let result = compute::<i64>(&formula, 1, &operators, &HashMap::new())
+                .unwrap_or_else(|errors| panic!("Couldn't compute the generated formula:\n{}", errors.join("\n")));
+            let elapsed = start.elapsed();
+            results.push(result);
+
+            println!("{:>8} bytes (with precendece={:?}): {:>10.3} ms, {:>6.1} ns per byte (result {})",
+                     formula.len(), with_operator_precedence,
+                     elapsed.as_secs_f64() * 1000., elapsed.as_nanos() as f64 / formula.len() as f64, result);
+
+            let start = std::time::Instant::now();
+            let original_result = compute_formula_original(&formula, with_operator_precedence);
+            let original_elapsed = start.elapsed();
+            assert_eq!(result, original_result, "The first solution should give the same result");
+
+            println!("{:>8} first solution:                {:>10.3} ms, {:>6.1} ns per byte ({:.1} times faster now)",
+                     "", original_elapsed.as_secs_f64() * 1000., original_elapsed.as_nanos() as f64 / formula.len() as f64,
+                     original_elapsed.as_secs_f64() / elapsed.as_secs_f64());
+        }
+        assert_eq!(results[0], results[1], "The generated formula should give the same result with both operator tables");
+    }
+}
+
+/// Generates a formula of about [size] bytes from [seed]
+///
+/// The formula is a sum of small bracketed terms like `((3 + 4) * 7)´ or `(7 * (3 + 4))´,
+/// s.t. it gives the same result with either operator table and never overflows. It only uses
+/// `+´ and `*´, s.t. [compute_formula_original] can compute it as well
+fn generate_formula(size: usize, seed: u64) -> String {
+    let mut random = Random::new(seed);
+    let mut next_number = |modulus: u64| random.below(modulus);
+
+    let mut formula = String::with_capacity(size + 64);
+    while formula.len() < size {
+        if !formula.is_empty() {
+            formula.push_str(" + ");
+        }
+        let (first, second, third) = (next_number(100), next_number(100), next_number(100));
+        if next_number(2) == 0 {
+            formula.push_str(&format!("(({} + {}) * {})", first, second, third));
+        } else {
+            formula.push_str(&format!("({} * ({} + {}))", third, first, second));
+        }
+    }
+
+    formula
+}
+
+/// The first solution of the task (a state machine reading the formula char by char), kept to
+/// compare the parser against in [task_benchmark]
+///
+/// Only knows `+´, `*´, brackets and non negative numbers and panics on anything else. Every
+/// bracket is computed by a recursive call which compiles the number regex again, and finding
+/// each char or closing bracket starts over at the beginning of the (sub) formula
+fn compute_formula_original(formula: &str, with_operator_precedence: bool) -> i64 {
+    /// Indicates what we are doing right now
+    enum State {
+        ReadsLeftOperand,
+        ReadsRightOperand,
+        ReadsOperator
+    }
 
     // Read position
     let mut idx = 0 as usize;
@@ -49,13 +144,12 @@ pub fn compute_formula(formula: &str, with_operator_precedence: bool) -> i64 {
         let curr_char = maybe_curr_char.unwrap();
 
         // Stores a value which was read in this step
-        let mut read_value: i64= 0;
+        let read_value: i64;
 
-        // println!("Current idx {}: {}", idx, curr_char);
         match curr_char {
             '(' => {
                 let inner_part_end_idx = read_until_matching_bracket(formula, idx + 1);
-                let inner_result = compute_formula(&formula[idx + 1..inner_part_end_idx + 1], with_operator_precedence);
+                let inner_result = compute_formula_original(&formula[idx + 1..inner_part_end_idx + 1], with_operator_precedence);
                 idx = inner_part_end_idx + 1;
                 read_value = inner_result;
             },
@@ -65,7 +159,6 @@ pub fn compute_formula(formula: &str, with_operator_precedence: bool) -> i64 {
 
                 read_value = number_match[0].parse::<i64>()
                     .expect(&format!("Couldn't parse number {} as integer", &number_match[0]));
-                // println!("Read value parse {}", read_value);
                 let match_size = number_match[0].len();
                 idx += match_size;
             },
@@ -75,54 +168,52 @@ pub fn compute_formula(formula: &str, with_operator_precedence: bool) -> i64 {
             },
             '+'  => {
                 curr_operator = curr_char;
+                read_value = 0;
             },
             '*' => {
                 if with_operator_precedence {
-                    let right_part = compute_formula(&formula[idx + 1..], with_operator_precedence);
+                    let right_part = compute_formula_original(&formula[idx + 1..], with_operator_precedence);
                     return curr_left_value * right_part;
                 } else {
-                    curr_operator = curr_char.clone();
+                    curr_operator = curr_char;
+                    read_value = 0;
                 }
             },
             _ => {
-                panic!(format!("Read unknown char `{}´ at index {}", curr_char, idx));
+                panic!("Read unknown char `{}´ at index {}", curr_char, idx);
             }
         }
 
-
         match state {
             State::ReadsLeftOperand => {
-                // println!("read value {}", read_value);
                 curr_left_value = read_value;
                 state = State::ReadsOperator;
             },
-
             State::ReadsRightOperand => {
                 curr_left_value = match curr_operator {
                     '+' => curr_left_value + read_value,
-                    '-' => curr_left_value - read_value,
                     '*' => curr_left_value * read_value,
                     _ => panic!("Operator unknown")
                 };
-                // println!("Left value {} right value {}", curr_left_value, read_value);
                 state = State::ReadsOperator;
             },
             State::ReadsOperator => {
                 state = State::ReadsRightOperand;
             }
         }
-        idx+=1;
+        idx += 1;
     }
 
     return curr_left_value;
 }
 
-/// Reads until it finds a closing bracket `)´
+/// Reads until it finds the closing bracket `)´ matching the one before [start_idx]
+/// (for [compute_formula_original])
 ///
 /// returns position BEFORE closing bracket
 fn read_until_matching_bracket(formula_part: &str, start_idx: usize) -> usize {
     let mut n_opening_brackets = 1 as u64;
-    for (offset, char) in formula_part.chars().skip(start_idx).into_iter().enumerate() {
+    for (offset, char) in formula_part.chars().skip(start_idx).enumerate() {
         if char == '(' {
             n_opening_brackets += 1;
         } else if char == ')' {
@@ -134,11 +225,952 @@ fn read_until_matching_bracket(formula_part: &str, start_idx: usize) -> usize {
         }
     }
 
-    panic!(format!("Didn't find matching closing bracket beginning from index {}", start_idx))
+    panic!("Didn't find matching closing bracket beginning from index {}", start_idx)
+}
+
+/// Generates homework of [n_lines] formulas like the task (`2 * 3 + (4 * (5 + 6))´) from [seed]
+///
+/// Each formula has at most 12 numbers (all single digits), s.t. it doesn't overflow with
+/// either set of rules
+pub fn generate_input(n_lines: usize, seed: u64) -> String {
+    let mut random = Random::new(seed);
+
+    let mut input = String::new();
+    for _ in 0..n_lines {
+        input.push_str(&generate_nested_formula(&mut random, 12, 2).0);
+        input.push('\n');
+    }
+
+    input
+}
+
+/// Generates a formula of 2 to 4 operands joined by `+´ or `*´ with at most [max_numbers] numbers
+/// (at least 2), where each operand may be a bracketed formula itself up to a depth of [max_depth]
+///
+/// returns the formula and the amount of numbers it used
+fn generate_nested_formula(random: &mut Random, max_numbers: usize, max_depth: usize) -> (String, usize) {
+    let n_operands = random.between(2, max_numbers.min(4) as u64) as usize;
+    let mut n_numbers = 0;
+
+    let mut formula = String::new();
+    for i in 0..n_operands {
+        if i > 0 {
+            formula.push_str(if random.one_in(2) { " + " } else { " * " });
+        }
+
+        // every operand after this one needs at least one number
+        let available = max_numbers - n_numbers - (n_operands - i - 1);
+        if max_depth > 0 && available >= 2 && random.one_in(3) {
+            let (inner, used) = generate_nested_formula(random, available.min(6), max_depth - 1);
+            formula.push_str(&format!("({})", inner));
+            n_numbers += used;
+        } else {
+            formula.push_str(&random.between(1, 9).to_string());
+            n_numbers += 1;
+        }
+    }
+
+    (formula, n_numbers)
+}
+
+/// A calculator reading formulas from stdin (`aoc calc´)
+///
+/// Each formula is computed with the rules of task 1 (left to right) and of task 2 (addition first)
+/// side by side. Results are kept as `_1´, `_2´, ... and can be used in later formulas.
+/// `:ast <formula>´ shows how the formula is grouped by both rules
+pub fn calculator() {
+    let rule_sets = [
+        ("left-to-right", OperatorTable::left_to_right()),
+        ("addition-first", OperatorTable::addition_first())
+    ];
+
+    // one set of history variables for each rule set
+    let mut history: Vec<HashMap<String, BigInt>> = vec![HashMap::new(); rule_sets.len()];
+    let is_interactive = std::io::stdin().is_terminal();
+
+    if is_interactive {
+        println!("Enter formulas (`:ast <formula>´ shows the parse tree, `:quit´ ends)");
+    }
+
+    let stdin = std::io::stdin();
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        if is_interactive {
+            print!("> ");
+            std::io::stdout().flush().expect("Couldn't write to stdout");
+        }
+
+        line.clear();
+        if stdin.lock().read_line(&mut line).expect("Couldn't read from stdin") == 0 {
+            break;
+        }
+        line_number += 1;
+        let line = line.trim_end_matches(|c| c == '\n' || c == '\r');
+        let input = line.trim();
+
+        if input.is_empty() {
+            continue;
+        } else if input == ":quit" || input == ":q" {
+            break;
+        } else if input.starts_with(":ast") {
+            let formula = &line[line.find(":ast").unwrap() + 4..];
+            for (name, operators) in rule_sets.iter() {
+                match Expression::parse(formula, operators) {
+                    Ok(expression) => println!("{:>16}: {}", name, expression),
+                    Err(errors) => {
+                        for err in errors {
+                            println!("{}", render_error(line_number, formula, &err.message, err.position, err.length));
+                        }
+                        break;
+                    }
+                }
+            }
+            continue;
+        } else if input.starts_with(':') {
+            println!("Unknown command `{}´ (known are `:ast <formula>´ and `:quit´)", input);
+            continue;
+        }
+
+        let results: Vec<Result<BigInt, Vec<String>>> = rule_sets
+            .iter()
+            .zip(history.iter())
+            .map(|((_, operators), variables)| compute::<BigInt>(line, line_number, operators, variables))
+            .collect();
+
+        if results.iter().all(|result| result.is_ok()) {
+            // remember the results as the next history variable
+            let name = format!("_{}", history[0].len() + 1);
+            let values: Vec<BigInt> = results.into_iter().map(|result| result.unwrap()).collect();
+            let columns: Vec<String> = rule_sets
+                .iter()
+                .zip(values.iter())
+                .map(|((rule_name, _), value)| format!("{}: {:<20}", rule_name, value))
+                .collect();
+            println!("{:<4} {}", name, columns.join(" ").trim_end());
+
+            for (variables, value) in history.iter_mut().zip(values.into_iter()) {
+                variables.insert(name.clone(), value);
+            }
+        } else {
+            // parse errors are the same for both rules, evaluation errors may differ
+            let reports: Vec<(&str, String)> = rule_sets
+                .iter()
+                .zip(results.into_iter())
+                .filter_map(|((rule_name, _), result)| result.err().map(|errors| (*rule_name, errors.join("\n"))))
+                .collect();
+
+            if reports.len() == rule_sets.len() && reports.iter().all(|(_, report)| *report == reports[0].1) {
+                println!("{}", reports[0].1);
+            } else {
+                for (rule_name, report) in reports {
+                    println!("({}) {}", rule_name, report);
+                }
+            }
+        }
+    }
+}
+
+/// One node of a parsed formula
+///
+/// Each node remembers the position (byte offset) of its number or operator within the formula,
+/// s.t. errors during the evaluation can point there
+#[derive(Debug, Clone)]
+enum Node {
+    Number { digits: String, position: usize },
+    Variable { name: String, position: usize },
+    /// negates the value computed before
+    Negation { position: usize },
+    /// combines the two values computed before
+    BinaryOperation { operator: char, position: usize }
+}
+
+/// The parsed formula as tree, stored in postfix order (operands before their operation)
+///
+/// Evaluating or dropping the formula walks the nodes in a single loop,
+/// s.t. long formulas don't need deep recursion
+#[derive(Debug, Clone)]
+pub struct Expression {
+    nodes: Vec<Node>
+}
+
+/// Shows the expression with explicit brackets around every operation, like `(1 + (2 * 3))´
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        for node in self.nodes.iter() {
+            let part = match node {
+                Node::Number { digits, .. } => digits.clone(),
+                Node::Variable { name, .. } => name.clone(),
+                Node::Negation { .. } => format!("(-{})", parts.pop().unwrap()),
+                Node::BinaryOperation { operator, .. } => {
+                    let right = parts.pop().unwrap();
+                    let left = parts.pop().unwrap();
+                    format!("({} {} {})", left, operator, right)
+                }
+            };
+            parts.push(part);
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// An error in a malformed formula (like an unbalanced bracket or a missing operand)
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub message: String,
+    pub position: usize,
+    pub length: usize
+}
+
+/// An error while evaluating an [Expression] (like an overflow or a division by zero)
+#[derive(Debug, Clone)]
+pub struct EvaluationError {
+    pub message: String,
+    pub position: usize
+}
+
+impl fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+/// The number types an [Expression] can be evaluated with
+///
+/// [i64] checks every operation for overflows, [BigInt] grows as needed
+pub trait Value: Sized + Clone + fmt::Display {
+    /// Parses the digits of a number, [None] if it doesn't fit
+    fn from_digits(digits: &str) -> Option<Self>;
+
+    /// Applies the unary minus, [None] if the result doesn't fit
+    fn negate(&self) -> Option<Self>;
+
+    /// Applies the binary [operator], fails with the reason (like `overflow´)
+    fn apply(operator: char, left: &Self, right: &Self) -> Result<Self, String>;
+}
+
+impl Value for i64 {
+    fn from_digits(digits: &str) -> Option<Self> {
+        digits.parse::<i64>().ok()
+    }
+
+    fn negate(&self) -> Option<Self> {
+        self.checked_neg()
+    }
+
+    fn apply(operator: char, left: &Self, right: &Self) -> Result<Self, String> {
+        let result = match operator {
+            '+' => left.checked_add(*right),
+            '-' => left.checked_sub(*right),
+            '*' => left.checked_mul(*right),
+            '/' | '%' if *right == 0 => return Err(String::from("division by zero")),
+            '/' => left.checked_div(*right),
+            '%' => left.checked_rem(*right),
+            '^' if *right < 0 => return Err(String::from("negative exponent")),
+            // 0, 1 and -1 stay small for any exponent
+            '^' if *left == 0 => Some(if *right == 0 { 1 } else { 0 }),
+            '^' if *left == 1 || *left == -1 => Some(if *right % 2 == 0 { 1 } else { *left }),
+            '^' => if *right > u32::max_value() as i64 { None } else { left.checked_pow(*right as u32) },
+            _ => return Err(format!("unknown operator `{}´", operator))
+        };
+
+        result.ok_or(String::from("overflow"))
+    }
+}
+
+/// Products and powers larger than this many bits are refused for [BigInt] (as overflow),
+/// otherwise stacked powers like `(9 ^ 999999) ^ 999999´ would eat up all memory and time
+const MAX_BIG_BITS: usize = 1 << 20;
+
+impl Value for BigInt {
+    fn from_digits(digits: &str) -> Option<Self> {
+        digits.parse::<BigInt>().ok()
+    }
+
+    fn negate(&self) -> Option<Self> {
+        Some(-self.clone())
+    }
+
+    fn apply(operator: char, left: &Self, right: &Self) -> Result<Self, String> {
+        match operator {
+            '+' => Ok(left + right),
+            '-' => Ok(left - right),
+            '*' if left.bits() + right.bits() > MAX_BIG_BITS => Err(String::from("overflow")),
+            '*' => Ok(left * right),
+            '/' | '%' if right.is_zero() => Err(String::from("division by zero")),
+            '/' => Ok(left / right),
+            '%' => Ok(left % right),
+            '^' => {
+                if *right < BigInt::zero() {
+                    return Err(String::from("negative exponent"));
+                }
+
+                // 0, 1 and -1 stay small for any exponent
+                if left.is_zero() {
+                    return Ok(if right.is_zero() { BigInt::one() } else { BigInt::zero() });
+                }
+                if left.abs().is_one() {
+                    return Ok(if right.is_even() { BigInt::one() } else { left.clone() });
+                }
+
+                // the result has at most `left.bits() * exponent´ bits
+                match right.to_usize() {
+                    Some(exponent) if left.bits().checked_mul(exponent).map_or(false, |bits| bits <= MAX_BIG_BITS) =>
+                        Ok(num::pow(left.clone(), exponent)),
+                    _ => Err(String::from("overflow"))
+                }
+            },
+            _ => Err(format!("unknown operator `{}´", operator))
+        }
+    }
+}
+
+/// In which direction operators of the same precedence are grouped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    /// `1 - 2 - 3´ means `(1 - 2) - 3´
+    Left,
+    /// `2 ^ 3 ^ 2´ means `2 ^ (3 ^ 2)´
+    Right
+}
+
+/// Defines the precedence (higher binds stronger) and associativity of each known operator
+///
+/// Known operators are `+´, `-´, `*´, `/´ (integer division), `%´ (remainder) and `^´ (power).
+/// The unary minus (`-3´, `-(1 + 2)´) has its own precedence: it negates everything on its
+/// right side which binds at least as strong
+#[derive(Debug, Clone)]
+pub struct OperatorTable {
+    operators: HashMap<char, (u8, Associativity)>,
+    unary_minus_precedence: u8
+}
+
+impl OperatorTable {
+    /// Creates a table without any operators
+    pub fn new() -> Self {
+        OperatorTable { operators: HashMap::new(), unary_minus_precedence: u8::max_value() }
+    }
+
+    /// Sets the precedence of the unary minus
+    pub fn with_unary_minus(mut self, precedence: u8) -> Self {
+        self.unary_minus_precedence = precedence;
+        self
+    }
+
+    /// Adds (or replaces) [operator] with the given [precedence] and [associativity]
+    pub fn with_operator(mut self, operator: char, precedence: u8, associativity: Associativity) -> Self {
+        self.operators.insert(operator, (precedence, associativity));
+        self
+    }
+
+    /// The rules of task 1: all operators are treated equal and evaluated left to right
+    /// (the unary minus only takes the operand right after it)
+    pub fn left_to_right() -> Self {
+        OperatorTable::new()
+            .with_operator('+', 1, Associativity::Left)
+            .with_operator('-', 1, Associativity::Left)
+            .with_operator('*', 1, Associativity::Left)
+            .with_operator('/', 1, Associativity::Left)
+            .with_operator('%', 1, Associativity::Left)
+            .with_operator('^', 1, Associativity::Left)
+            .with_unary_minus(2)
+    }
+
+    /// The rules of task 2: `+´ (and `-´) are evaluated before `*´ (and `/´, `%´)
+    ///
+    /// `^´ binds strongest (grouped from the right), the unary minus right below it,
+    /// s.t. `-2 ^ 2´ is `-(2 ^ 2)´
+    pub fn addition_first() -> Self {
+        OperatorTable::new()
+            .with_operator('+', 2, Associativity::Left)
+            .with_operator('-', 2, Associativity::Left)
+            .with_operator('*', 1, Associativity::Left)
+            .with_operator('/', 1, Associativity::Left)
+            .with_operator('%', 1, Associativity::Left)
+            .with_operator('^', 4, Associativity::Right)
+            .with_unary_minus(3)
+    }
+
+    fn get(&self, operator: char) -> Option<(u8, Associativity)> {
+        self.operators.get(&operator).copied()
+    }
+}
+
+impl Expression {
+    /// Parses [formula] into an expression tree according to the rules in [operators]
+    ///
+    /// returns all errors found in the formula (ordered by their position) if it is malformed
+    pub fn parse(formula: &str, operators: &OperatorTable) -> Result<Expression, Vec<ParseError>> {
+        let mut nodes: Vec<Node> = Vec::new();
+        let errors = parse_formula(formula, operators, &mut nodes);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Expression { nodes })
+    }
+
+    /// Computes the value of the expression with the number type [V]
+    ///
+    /// fails on overflows, a division by zero or a negative exponent
+    /// and tells where in the formula it happened
+    pub fn evaluate<V: Value>(&self) -> Result<V, EvaluationError> {
+        self.evaluate_with_variables(&HashMap::new())
+    }
+
+    /// Same as [evaluate] but variables (like `_1´) are looked up in [variables]
+    pub fn evaluate_with_variables<V: Value>(&self, variables: &HashMap<String, V>) -> Result<V, EvaluationError> {
+        let mut evaluator = Evaluator::new(variables);
+        for node in self.nodes.iter() {
+            match node {
+                Node::Number { digits, position } => evaluator.number(digits, *position),
+                Node::Variable { name, position } => evaluator.variable(name, *position),
+                Node::Negation { position } => evaluator.negation(*position),
+                Node::BinaryOperation { operator, position } => evaluator.binary_operation(*operator, *position)
+            }
+        }
+
+        evaluator.finish()
+    }
+}
+
+/// Receives the parts of a formula in postfix order (operands before their operation)
+/// from [parse_formula]
+trait PostfixSink {
+    fn number(&mut self, digits: &str, position: usize);
+    fn variable(&mut self, name: &str, position: usize);
+    fn negation(&mut self, position: usize);
+    fn binary_operation(&mut self, operator: char, position: usize);
+}
+
+/// Collects the nodes of an [Expression]
+impl PostfixSink for Vec<Node> {
+    fn number(&mut self, digits: &str, position: usize) {
+        self.push(Node::Number { digits: String::from(digits), position });
+    }
+
+    fn variable(&mut self, name: &str, position: usize) {
+        self.push(Node::Variable { name: String::from(name), position });
+    }
+
+    fn negation(&mut self, position: usize) {
+        self.push(Node::Negation { position });
+    }
+
+    fn binary_operation(&mut self, operator: char, position: usize) {
+        self.push(Node::BinaryOperation { operator, position });
+    }
+}
+
+/// Computes each part of a formula as soon as it arrives, every operation finds its operands
+/// on the stack [values]
+///
+/// Stops at the first error and ignores everything after it
+struct Evaluator<'a, V: Value> {
+    values: Vec<V>,
+    variables: &'a HashMap<String, V>,
+    error: Option<EvaluationError>
+}
+
+impl<'a, V: Value> Evaluator<'a, V> {
+    fn new(variables: &'a HashMap<String, V>) -> Evaluator<'a, V> {
+        Evaluator { values: Vec::new(), variables, error: None }
+    }
+
+    fn push(&mut self, value: Result<V, EvaluationError>) {
+        match value {
+            Ok(value) => self.values.push(value),
+            Err(err) => self.error = Some(err)
+        }
+    }
+
+    /// returns the value of the whole formula or the first error
+    fn finish(mut self) -> Result<V, EvaluationError> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.values.pop().expect("A formula has at least one operand"))
+        }
+    }
+}
+
+impl<'a, V: Value> PostfixSink for Evaluator<'a, V> {
+    fn number(&mut self, digits: &str, position: usize) {
+        if self.error.is_none() {
+            let value = V::from_digits(digits)
+                .ok_or_else(|| EvaluationError { message: format!("number {} too large", digits), position });
+            self.push(value);
+        }
+    }
+
+    fn variable(&mut self, name: &str, position: usize) {
+        if self.error.is_none() {
+            let value = self.variables.get(name)
+                .cloned()
+                .ok_or_else(|| EvaluationError { message: format!("unknown variable `{}´", name), position });
+            self.push(value);
+        }
+    }
+
+    fn negation(&mut self, position: usize) {
+        if self.error.is_none() {
+            let value = self.values.pop().unwrap()
+                .negate()
+                .ok_or_else(|| EvaluationError { message: String::from("overflow in unary `-´"), position });
+            self.push(value);
+        }
+    }
+
+    fn binary_operation(&mut self, operator: char, position: usize) {
+        if self.error.is_none() {
+            let right = self.values.pop().unwrap();
+            let left = self.values.pop().unwrap();
+            let value = V::apply(operator, &left, &right).map_err(|reason| EvaluationError {
+                message: format!("{} in `{} {} {}´", reason, shorten_number(&left), operator, shorten_number(&right)),
+                position
+            });
+            self.push(value);
+        }
+    }
+}
+
+/// Shows [value] within error messages, numbers with more than 30 digits are cut in the middle
+fn shorten_number<V: Value>(value: &V) -> String {
+    let digits = value.to_string();
+    if digits.len() <= 30 {
+        return digits;
+    }
+
+    format!("{}...{} ({} digits)", &digits[..12], &digits[digits.len() - 12..], digits.len())
+}
+
+/// computes the equation
+///
+/// if [with_operator_precedence] is [true] we will execute + before *
+/// otherwise all operators are treated equal
+pub fn compute_formula(formula: &str, with_operator_precedence: bool) -> Result<i64, String> {
+    compute::<i64>(formula, 1, &get_operator_table(with_operator_precedence), &HashMap::new())
+        .map_err(|errors| errors.join("\n"))
+}
+
+/// Same as [compute_formula] but with arbitrary precision
+pub fn compute_formula_big(formula: &str, with_operator_precedence: bool) -> Result<BigInt, String> {
+    compute::<BigInt>(formula, 1, &get_operator_table(with_operator_precedence), &HashMap::new())
+        .map_err(|errors| errors.join("\n"))
+}
+
+/// Same as [compute_formula] by rewriting the formula as a string instead of parsing it, an
+/// independent evaluator to check the parser against
+///
+/// Only knows `+´, `*´, brackets and non negative numbers (separated by spaces like in the
+/// input), panics on anything else. The innermost bracket is replaced by its value until no
+/// bracket is left
+pub fn compute_formula_naive(formula: &str, with_operator_precedence: bool) -> i64 {
+    let mut formula = String::from(formula);
+
+    while let Some(open) = formula.rfind('(') {
+        let close = open + formula[open..].find(')').expect("Unclosed bracket");
+        let value = compute_flat_formula(&formula[open + 1..close], with_operator_precedence);
+        formula = format!("{}{}{}", &formula[..open], value, &formula[close + 1..]);
+    }
+
+    compute_flat_formula(&formula, with_operator_precedence)
+}
+
+/// Computes a [formula] without brackets like `1 + 2 * 3´
+fn compute_flat_formula(formula: &str, with_operator_precedence: bool) -> i64 {
+    let parse = |number: &str| number.trim().parse::<i64>().expect(&format!("Couldn't parse {}", number));
+
+    if with_operator_precedence {
+        // the sums between the products are computed first
+        return formula
+            .split('*')
+            .map(|product| product.split('+').map(parse).sum::<i64>())
+            .product();
+    }
+
+    let tokens: Vec<&str> = formula.split_whitespace().collect();
+    let mut result = parse(tokens[0]);
+    for operation in tokens[1..].chunks(2) {
+        match operation[0] {
+            "+" => result += parse(operation[1]),
+            "*" => result *= parse(operation[1]),
+            operator => panic!("Unknown operator {}", operator)
+        }
+    }
+
+    result
+}
+
+/// Computes every (non empty) line of [input]
+///
+/// If any line fails, the errors of all lines are reported at once (each with its line, column
+/// and the formula underlined at the error)
+pub fn compute_lines<V: Value>(input: &str, operators: &OperatorTable) -> Result<Vec<V>, String> {
+    let mut results: Vec<V> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match compute::<V>(line, i + 1, operators, &HashMap::new()) {
+            Ok(result) => results.push(result),
+            Err(mut line_errors) => errors.append(&mut line_errors)
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+
+    Ok(results)
+}
+
+/// Parses and evaluates one [formula] which is line [line_number] of its input
+/// (with the values of [variables])
+///
+/// returns the rendered errors (see [render_error])
+fn compute<V: Value>(formula: &str, line_number: usize, operators: &OperatorTable, variables: &HashMap<String, V>) -> Result<V, Vec<String>> {
+    // evaluated right while parsing, s.t. no tree is built
+    let mut evaluator = Evaluator::new(variables);
+    let errors = parse_formula(formula, operators, &mut evaluator);
+    if !errors.is_empty() {
+        return Err(errors
+            .iter()
+            .map(|err| render_error(line_number, formula, &err.message, err.position, err.length))
+            .collect());
+    }
+
+    evaluator.finish().map_err(|err| vec![render_error(line_number, formula, &err.message, err.position, 1)])
+}
+
+/// Gets the preset of the task (task 2 if [with_operator_precedence])
+fn get_operator_table(with_operator_precedence: bool) -> OperatorTable {
+    if with_operator_precedence {
+        OperatorTable::addition_first()
+    } else {
+        OperatorTable::left_to_right()
+    }
+}
+
+/// An operation of [parse_formula] still waiting for (the end of) its right operand
+#[derive(Debug, Clone, Copy)]
+enum PendingOperation {
+    /// the right operand takes all operators binding at least with [min_precedence]
+    Binary { operator: char, position: usize, min_precedence: u16 },
+    Negation { position: usize, min_precedence: u16 },
+    /// waits for its `)´
+    OpenBracket { position: usize }
+}
+
+impl PendingOperation {
+    /// Hands the operation to [sink] (as its right operand is complete)
+    fn complete<S: PostfixSink>(self, sink: &mut S) {
+        match self {
+            PendingOperation::Binary { operator, position, .. } => sink.binary_operation(operator, position),
+            PendingOperation::Negation { position, .. } => sink.negation(position),
+            PendingOperation::OpenBracket { .. } => ()
+        }
+    }
+}
+
+/// Reads [formula] byte by byte in a single pass and hands its parts in postfix order to [sink]
+/// according to the rules in [operators] (shunting-yard)
+///
+/// Operations wait on an explicit stack until their right operand is complete, s.t. brackets
+/// may be nested arbitrarily deep. It does not stop at the first error but notes it down and
+/// goes on (as if the missing operand was there), s.t. all problems of a formula are reported
+/// at once. Nothing is handed to [sink] after the first error
+///
+/// returns all errors found in the formula (ordered by their position)
+fn parse_formula<S: PostfixSink>(formula: &str, operators: &OperatorTable, sink: &mut S) -> Vec<ParseError> {
+    let bytes = formula.as_bytes();
+    let mut pending: Vec<PendingOperation> = Vec::new();
+    let mut errors: Vec<ParseError> = Vec::new();
+
+    // how many brackets are currently open
+    let mut depth = 0 as usize;
+    // an operand comes next (otherwise an operator or `)´)
+    let mut expects_operand = true;
+    // the last thing read was an operator (to tell doubled operators from missing operands)
+    let mut follows_operator = false;
+
+    let mut idx = 0 as usize;
+    while idx < bytes.len() {
+        let position = idx;
+        match bytes[idx] {
+            b' ' | b'\t' | b'\r' => {
+                idx += 1;
+                continue;
+            },
+            b'0' ..= b'9' | b'_' | b'(' if !expects_operand => {
+                // two operands after each other, skip the second one
+                errors.push(ParseError { message: String::from("missing operator"), position, length: 1 });
+                expects_operand = true;
+                continue;
+            },
+            b'0' ..= b'9' => {
+                // read all following digits
+                while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+                    idx += 1;
+                }
+                if errors.is_empty() {
+                    sink.number(&formula[position..idx], position);
+                }
+                expects_operand = false;
+                follows_operator = false;
+            },
+            b'_' => {
+                // read the whole name
+                idx += 1;
+                while idx < bytes.len() && (bytes[idx].is_ascii_alphanumeric() || bytes[idx] == b'_') {
+                    idx += 1;
+                }
+                if errors.is_empty() {
+                    sink.variable(&formula[position..idx], position);
+                }
+                expects_operand = false;
+                follows_operator = false;
+            },
+            b'(' => {
+                pending.push(PendingOperation::OpenBracket { position });
+                depth += 1;
+                idx += 1;
+                follows_operator = false;
+            },
+            b'-' if expects_operand => {
+                let min_precedence = operators.unary_minus_precedence as u16;
+                pending.push(PendingOperation::Negation { position, min_precedence });
+                idx += 1;
+                follows_operator = true;
+            },
+            byte @ (b'+' | b'-' | b'*' | b'/' | b'%' | b'^' | b')') if expects_operand => {
+                // the operator or bracket is read again as if the operand was there
+                let message = if byte == b')' {
+                    String::from("missing operand before `)´")
+                } else if follows_operator {
+                    format!("doubled operator `{}´", byte as char)
+                } else {
+                    format!("missing operand before `{}´", byte as char)
+                };
+                errors.push(ParseError { message, position, length: 1 });
+                expects_operand = false;
+            },
+            byte @ (b'+' | b'-' | b'*' | b'/' | b'%' | b'^') => {
+                let operator = byte as char;
+                idx += 1;
+                expects_operand = true;
+                follows_operator = true;
+
+                let (precedence, associativity) = match operators.get(operator) {
+                    Some(rule) => rule,
+                    None => {
+                        // skip the operator together with its right operand
+                        errors.push(ParseError { message: format!("unknown operator `{}´", operator), position, length: 1 });
+                        continue;
+                    }
+                };
+
+                // complete all operations whose right operand ends here
+                while let Some(&operation) = pending.last() {
+                    match operation {
+                        PendingOperation::Binary { min_precedence, .. }
+                        | PendingOperation::Negation { min_precedence, .. } if min_precedence > precedence as u16 => {
+                            pending.pop();
+                            if errors.is_empty() {
+                                operation.complete(sink);
+                            }
+                        },
+                        _ => break
+                    }
+                }
+
+                // left associative operators must not take the same precedence on their right side
+                let min_precedence = match associativity {
+                    Associativity::Left => precedence as u16 + 1,
+                    Associativity::Right => precedence as u16
+                };
+                pending.push(PendingOperation::Binary { operator, position, min_precedence });
+            },
+            b')' => {
+                idx += 1;
+                follows_operator = false;
+                if depth == 0 {
+                    errors.push(ParseError { message: String::from("unmatched `)´"), position, length: 1 });
+                    continue;
+                }
+
+                // complete everything within the brackets
+                while let Some(operation) = pending.pop() {
+                    if let PendingOperation::OpenBracket { .. } = operation {
+                        break;
+                    }
+                    if errors.is_empty() {
+                        operation.complete(sink);
+                    }
+                }
+                depth -= 1;
+            },
+            _ => {
+                // skip the whole (maybe multi byte) character, probably meant as operand
+                // or as operator (then skip its right operand as well)
+                let unknown = formula[idx..].chars().next().unwrap();
+                errors.push(ParseError { message: format!("unexpected character `{}´", unknown), position, length: 1 });
+                idx += unknown.len_utf8();
+                expects_operand = !expects_operand;
+                follows_operator = false;
+            }
+        }
+    }
+
+    if expects_operand {
+        errors.push(ParseError { message: String::from("missing operand at the end"), position: formula.len(), length: 1 });
+    }
+
+    // complete the rest
+    while let Some(operation) = pending.pop() {
+        if let PendingOperation::OpenBracket { position } = operation {
+            errors.push(ParseError { message: String::from("unclosed `(´"), position, length: 1 });
+        } else if errors.is_empty() {
+            operation.complete(sink);
+        }
+    }
+
+    errors.sort_by_key(|error| error.position);
+    errors
+}
+
+/// Renders an error [message] pointing to [position] (byte offset) of [formula], which is
+/// line [line_number] (starting at 1) of the input, like
+///
+/// ```text
+/// line 2, column 5: doubled operator `*´
+///     2 | 1 + * 3
+///       |     ^
+/// ```
+fn render_error(line_number: usize, formula: &str, message: &str, position: usize, length: usize) -> String {
+    // columns count characters, not bytes
+    let column = formula[..position.min(formula.len())].chars().count();
+    let line_label = line_number.to_string();
+
+    format!("line {}, column {}: {}\n{:>5} | {}\n{:>5} | {}{}",
+            line_number, column + 1, message,
+            line_label, formula,
+            "", " ".repeat(column), "^".repeat(length.max(1)))
 }
 
 
 /// Reads the file into the binary
 fn get_input_data() -> &'static str {
     return include_str!("input.txt");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Formulas of the task, small enough not to overflow (at most 16 numbers below 10)
+    fn formula() -> impl Strategy<Value = String> {
+        let number = (1i64..10).prop_map(|n| n.to_string());
+        number.prop_recursive(4, 16, 2, |inner| {
+            (prop::collection::vec((inner, prop::sample::select(vec!["+", "*"])), 2), any::<bool>())
+                .prop_map(|(terms, bracketed)| {
+                    let mut formula = terms[0].0.clone();
+                    for (term, operator) in terms.iter().skip(1) {
+                        // the operator of a term is the one in front of it
+                        formula = format!("{} {} {}", formula, operator, term);
+                    }
+                    if bracketed { format!("({})", formula) } else { formula }
+                })
+        })
+    }
+
+    #[test]
+    fn powers_of_0_1_and_minus_1_never_overflow() {
+        assert_eq!(compute_formula("0 ^ 99999999999", true), Ok(0));
+        assert_eq!(compute_formula("1 ^ 99999999999", true), Ok(1));
+        assert_eq!(compute_formula("(0 - 1) ^ 99999999999", true), Ok(-1));
+        assert_eq!(compute_formula("(0 - 1) ^ 99999999998", true), Ok(1));
+        assert_eq!(compute_formula_big("(0 - 1) ^ 999999999999999999999", true), Ok(BigInt::from(-1)));
+    }
+
+    #[test]
+    fn huge_big_integer_powers_overflow() {
+        for formula in ["(9 ^ 9999) ^ 9999", "(9 ^ 999999) ^ 999999", "2 ^ 99999999999999999999"].iter() {
+            let err = compute_formula_big(formula, true).unwrap_err();
+            assert!(err.contains("overflow"), "{}", err);
+        }
+        assert_eq!(compute_formula_big("2 ^ 100", true), Ok(BigInt::one() << 100));
+    }
+
+    /// The message and position of each error in [formula]
+    fn parse_errors(formula: &str) -> Vec<(String, usize)> {
+        Expression::parse(formula, &OperatorTable::addition_first())
+            .unwrap_err()
+            .into_iter()
+            .map(|error| (error.message, error.position))
+            .collect()
+    }
+
+    #[test]
+    fn parse_errors_name_the_problem_and_its_position() {
+        assert_eq!(parse_errors("1 + * 3"), vec![(String::from("doubled operator `*´"), 4)]);
+        assert_eq!(parse_errors("2 * (1 + 2"), vec![(String::from("unclosed `(´"), 4)]);
+        assert_eq!(parse_errors("1 + 2) * 3"), vec![(String::from("unmatched `)´"), 5)]);
+        assert_eq!(parse_errors("1 + 2 *"), vec![(String::from("missing operand at the end"), 7)]);
+        assert_eq!(parse_errors("1 + a * 3"), vec![(String::from("unexpected character `a´"), 4)]);
+
+        // all errors are reported at once, ordered by their position
+        assert_eq!(parse_errors("(1 ? 2 + + 3"), vec![
+            (String::from("unclosed `(´"), 0),
+            (String::from("unexpected character `?´"), 3),
+            (String::from("doubled operator `+´"), 9)
+        ]);
+    }
+
+    #[test]
+    fn deeply_nested_formulas_are_computed() {
+        let depth = 100000;
+        let brackets = format!("{}1 + 2{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(compute_formula(&brackets, true), Ok(3));
+
+        let negations = format!("{}7", "-".repeat(depth + 1));
+        assert_eq!(compute_formula(&negations, true), Ok(-7));
+
+        let powers = format!("{}1", "1 ^ ".repeat(depth));
+        assert_eq!(compute_formula(&powers, true), Ok(1));
+
+        let unclosed = format!("{}1", "(".repeat(depth));
+        assert_eq!(parse_errors(&unclosed).len(), depth);
+    }
+
+    #[test]
+    fn rendered_errors_point_to_the_column() {
+        let render = |formula: &str| compute::<i64>(formula, 2, &OperatorTable::addition_first(), &HashMap::new()).unwrap_err();
+
+        assert_eq!(render("1 + * 3"), vec![String::from(
+            "line 2, column 5: doubled operator `*´\n    2 | 1 + * 3\n      |     ^")]);
+        assert_eq!(render("(1 + 2"), vec![String::from(
+            "line 2, column 1: unclosed `(´\n    2 | (1 + 2\n      | ^")]);
+        assert_eq!(render("1 +"), vec![String::from(
+            "line 2, column 4: missing operand at the end\n    2 | 1 +\n      |    ^")]);
+
+        // columns count characters, not bytes
+        assert_eq!(render("ä + + 1"), vec![
+            String::from("line 2, column 1: unexpected character `ä´\n    2 | ä + + 1\n      | ^"),
+            String::from("line 2, column 5: doubled operator `+´\n    2 | ä + + 1\n      |     ^")
+        ]);
+    }
+
+    proptest! {
+        #[test]
+        fn compute_formula_agrees_with_naive(formula in formula(), with_operator_precedence in any::<bool>()) {
+            prop_assert_eq!(compute_formula(&formula, with_operator_precedence),
+                            Ok(compute_formula_naive(&formula, with_operator_precedence)));
+        }
+    }
 }
\ No newline at end of file
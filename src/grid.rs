@@ -0,0 +1,307 @@
+use std::fmt;
+
+/// Which fields count as neighbours of a field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Adjacency {
+    /// the 4 fields sharing an edge
+    Orthogonal,
+    /// the 8 fields sharing an edge or a corner
+    All
+}
+
+impl Adjacency {
+    /// The offsets (dx, dy) of the neighbours, clockwise starting north
+    /// (y grows downwards, like the lines of an input)
+    fn get_offsets(&self) -> &'static [(i64, i64)] {
+        match self {
+            Adjacency::Orthogonal => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+            Adjacency::All => &[(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)]
+        }
+    }
+}
+
+/// A rectangular map of fields, like the ones most tasks come as input
+///
+/// [x] is the column (left to right), [y] the row (top to bottom), both starting at 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+
+    /// the fields row by row
+    cells: Vec<T>
+}
+
+impl<T> Grid<T> {
+    /// Parses a map like
+    ///
+    /// ```text
+    /// ..##.
+    /// #...#
+    /// ```
+    ///
+    /// where [to_cell] translates each character into a field (or [None] if it is not allowed).
+    /// Empty lines are skipped and spaces are fields like any other character (only a `\r´ at the
+    /// end of a line is dropped). Fails if the rows are not equally wide or on an unknown character
+    pub fn parse<F: Fn(char) -> Option<T>>(input: &str, to_cell: F) -> Result<Grid<T>, String> {
+        let mut rows: Vec<Vec<T>> = Vec::new();
+
+        for (i, line) in input.lines().map(|line| line.trim_end_matches('\r')).enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(col, value)| to_cell(value)
+                    .ok_or_else(|| format!("Unknown field `{}´ in line {}, column {}", value, i + 1, col + 1)))
+                .collect::<Result<Vec<T>, String>>()?;
+
+            if let Some(first_row) = rows.first() {
+                if row.len() != first_row.len() {
+                    return Err(format!("Line {} is {} fields wide but the first line is {} fields wide",
+                                       i + 1, row.len(), first_row.len()));
+                }
+            }
+            rows.push(row);
+        }
+
+        Grid::from_rows(rows)
+    }
+
+    /// Creates a grid from its rows (top to bottom)
+    ///
+    /// fails if the rows are not equally wide or there are no fields at all (rows count from 1)
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, String> {
+        let width = match rows.first() {
+            Some(first_row) if !first_row.is_empty() => first_row.len(),
+            _ => return Err(String::from("The grid is empty"))
+        };
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            return Err(format!("Row {} is {} fields wide but the first row is {} fields wide", y + 1, rows[y].len(), width));
+        }
+
+        let height = rows.len();
+        let cells: Vec<T> = rows.into_iter().flatten().collect();
+
+        Ok(Grid { width, height, cells })
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether ([x], [y]) is a field of the grid
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    /// Gets the field at ([x], [y]) or [None] if it is outside of the grid
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Sets the field at ([x], [y]), panics if it is outside of the grid
+    pub fn set(&mut self, x: usize, y: usize, value: T) {
+        let (width, height) = (self.width, self.height);
+        *self.get_mut(x, y).unwrap_or_else(|| panic!("({}, {}) is outside of the {}x{} grid", x, y, width, height)) = value;
+    }
+
+    /// Gets the field at ([x], [y]) pretending the grid repeats endlessly in every direction
+    pub fn get_wrapping(&self, x: i64, y: i64) -> &T {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        &self.cells[y * self.width + x]
+    }
+
+    /// Iterates over the rows from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// Iterates over all positions (x, y) row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |i| (i % width, i / width))
+    }
+
+    /// Iterates over the positions of the neighbours of ([x], [y]) which are within the grid
+    pub fn neighbours(&self, x: usize, y: usize, adjacency: Adjacency) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width as i64, self.height as i64);
+
+        adjacency.get_offsets().iter().filter_map(move |(dx, dy)| {
+            let (x, y) = (x as i64 + dx, y as i64 + dy);
+            if x >= 0 && y >= 0 && x < width && y < height {
+                Some((x as usize, y as usize))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Iterates over the positions of the neighbours of ([x], [y]) pretending the grid repeats
+    /// endlessly (so there always are 4 or 8 of them, on tiny grids some may be the same field)
+    pub fn neighbours_wrapping(&self, x: usize, y: usize, adjacency: Adjacency) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width as i64, self.height as i64);
+
+        adjacency.get_offsets().iter().map(move |(dx, dy)| {
+            ((x as i64 + dx).rem_euclid(width) as usize, (y as i64 + dy).rem_euclid(height) as usize)
+        })
+    }
+
+    /// Creates a grid of the same size with [to_cell] applied to every field
+    pub fn map<U, F: Fn(&T) -> U>(&self, to_cell: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(to_cell).collect()
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a [width] x [height] grid with every field set to [value]
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T> {
+        assert!(width > 0 && height > 0, "A grid needs at least one field");
+        Grid { width, height, cells: vec![value; width * height] }
+    }
+
+    /// Mirrors the grid at its main diagonal (rows become columns)
+    pub fn transpose(&self) -> Grid<T> {
+        self.rearrange(self.height, self.width, |x, y| (y, x))
+    }
+
+    /// Rotates the grid by 90° clockwise
+    pub fn rotate_clockwise(&self) -> Grid<T> {
+        let height = self.height;
+        self.rearrange(self.height, self.width, |x, y| (y, height - 1 - x))
+    }
+
+    /// Rotates the grid by 90° counter clockwise
+    pub fn rotate_counter_clockwise(&self) -> Grid<T> {
+        let width = self.width;
+        self.rearrange(self.height, self.width, |x, y| (width - 1 - y, x))
+    }
+
+    /// Creates a [width] x [height] grid where the field (x, y) is taken from the position
+    /// [source] gives for it
+    fn rearrange<F: Fn(usize, usize) -> (usize, usize)>(&self, width: usize, height: usize, source: F) -> Grid<T> {
+        let cells = (0..width * height)
+            .map(|i| {
+                let (x, y) = source(i % width, i / width);
+                self.cells[y * self.width + x].clone()
+            })
+            .collect();
+
+        Grid { width, height, cells }
+    }
+}
+
+/// Shows the grid row by row (like it is given in the input)
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3x2 grid, non-square s.t. mixing up width and height shows
+    fn letters() -> Grid<char> {
+        Grid::parse("abc\ndef\n", Some).unwrap()
+    }
+
+    #[test]
+    fn rotates_non_square_grids() {
+        let grid = letters();
+
+        let clockwise = grid.rotate_clockwise();
+        assert_eq!((clockwise.get_width(), clockwise.get_height()), (2, 3));
+        assert_eq!(clockwise.to_string(), "da\neb\nfc");
+
+        let counter_clockwise = grid.rotate_counter_clockwise();
+        assert_eq!((counter_clockwise.get_width(), counter_clockwise.get_height()), (2, 3));
+        assert_eq!(counter_clockwise.to_string(), "cf\nbe\nad");
+
+        assert_eq!(clockwise.rotate_counter_clockwise(), grid);
+        assert_eq!(clockwise.rotate_clockwise(), counter_clockwise.rotate_counter_clockwise());
+        assert_eq!(clockwise.rotate_clockwise().rotate_clockwise().rotate_clockwise(), grid);
+    }
+
+    #[test]
+    fn transposes() {
+        let grid = letters();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(grid.transpose().transpose(), grid);
+    }
+
+    #[test]
+    fn neighbours_stay_within_the_grid() {
+        let grid = letters();
+        let neighbours = |x, y, adjacency| grid.neighbours(x, y, adjacency).collect::<Vec<(usize, usize)>>();
+
+        assert_eq!(neighbours(0, 0, Adjacency::Orthogonal), vec![(1, 0), (0, 1)]);
+        assert_eq!(neighbours(0, 0, Adjacency::All), vec![(1, 0), (1, 1), (0, 1)]);
+        assert_eq!(neighbours(1, 1, Adjacency::All), vec![(1, 0), (2, 0), (2, 1), (0, 1), (0, 0)]);
+        assert_eq!(Grid::new(3, 3, 0).neighbours(1, 1, Adjacency::All).count(), 8);
+    }
+
+    #[test]
+    fn spaces_are_fields() {
+        let grid = Grid::parse(" #\r\n# \r\n", |field| match field {
+            '#' => Some(true),
+            ' ' => Some(false),
+            _ => None
+        }).unwrap();
+        assert_eq!(grid.map(|&wall| if wall { '#' } else { '.' }).to_string(), ".#\n#.");
+    }
+
+    #[test]
+    fn errors_count_lines_and_rows_from_1() {
+        assert_eq!(Grid::parse("ab\n\nabc\n", Some), Err(String::from("Line 3 is 3 fields wide but the first line is 2 fields wide")));
+        assert_eq!(Grid::parse("ab\na?", |field| if field == '?' { None } else { Some(field) }),
+                   Err(String::from("Unknown field `?´ in line 2, column 2")));
+        assert_eq!(Grid::from_rows(vec![vec![1, 2], vec![3]]), Err(String::from("Row 2 is 1 fields wide but the first row is 2 fields wide")));
+    }
+
+    #[test]
+    fn wraps_around() {
+        let grid = letters();
+        assert_eq!(*grid.get_wrapping(-1, -1), 'f');
+        assert_eq!(*grid.get_wrapping(3, 2), 'a');
+        assert_eq!(*grid.get_wrapping(7, -3), 'e');
+        assert_eq!(grid.get(3, 0), None);
+
+        assert_eq!(grid.neighbours_wrapping(0, 0, Adjacency::Orthogonal).collect::<Vec<(usize, usize)>>(),
+                   vec![(0, 1), (1, 0), (0, 1), (2, 0)]);
+        assert_eq!(grid.neighbours_wrapping(2, 1, Adjacency::All).count(), 8);
+    }
+}
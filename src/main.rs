@@ -2,23 +2,70 @@
 #![feature(type_alias_impl_trait)]
 
 pub mod files;
-// #[path="day12/solution.rs"] mod day12;
-//#[path="day13/solution.rs"] mod day13;
-// #[path="day03/solution.rs"] mod day03;
-//#[path="day02/solution.rs"] mod day02;
-// #[path="day14/solution.rs"] mod day14;
-// #[path="day15/solution.rs"] mod day15;
-//#[path="day16/solution.rs"] mod day16;
-// #[path="day17/solution.rs"] mod day17;
+pub mod grid;
+pub mod math;
+pub mod point;
+pub mod random;
+#[path="day12/solution.rs"] mod day12;
+#[path="day13/solution.rs"] mod day13;
+#[path="day03/solution.rs"] mod day03;
+#[path="day02/solution.rs"] mod day02;
+#[path="day14/solution.rs"] mod day14;
+#[path="day15/solution.rs"] mod day15;
+#[path="day16/solution.rs"] mod day16;
+#[path="day17/solution.rs"] mod day17;
 #[path="day18/solution.rs"] mod day18;
 
 fn main() {
+    // subcommands (like `aoc calc´) run on their own
+    match std::env::args().nth(1).as_deref() {
+        Some("calc") => {
+            day18::calculator();
+            return;
+        },
+        Some("passwords") => {
+            // aoc passwords <count|position> [file] [--verdicts]
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let show_verdicts = args.iter().any(|arg| arg == "--verdicts");
+            let positional: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+            let policy = positional.get(0).map_or("count", |policy| policy.as_str());
+            let filename = positional.get(1).map(|filename| filename.as_str());
+
+            if let Err(err) = day02::task_stream(policy, filename, show_verdicts) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+            return;
+        },
+        Some("generate") => {
+            // aoc generate <day> [size] [seed]
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            match generate_input(&args) {
+                Ok(input) => print!("{}", input),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        },
+        Some(command) => {
+            eprintln!("Unknown command `{}´ (known are `calc´, `generate´ and `passwords´)", command);
+            std::process::exit(1);
+        },
+        None => {}
+    }
+
     // To run a specific day, just uncomment the task you need
     // and also include the current day
 
     // day02::task1();
     // day02::task2();
+    // day02::task_audit();
+    // day03::task1();
     // day03::task2();
+    // day03::task_best_slope(7);
+    // day03::task_render_path(3, 1);
     // day12::task1();
 
     // day13::task1();
@@ -32,10 +79,44 @@ fn main() {
 
     // day16::task1();
     // day16::task2();
+    // day16::report(day16::ReportFormat::Table);
 
     // day17::task1();
     // day17::task2();
+    // day17::task_with_dimensions(5);
+    // day17::task_benchmark();
+    // day17::task_print(3);
+    // day17::task_export(4, "day17_export", day17::ExportFormat::Pgm);
+    // day17::task_with_rule(3, "B36/S23", day17::Neighbourhood::VonNeumann(1));
 
     day18::task1(false);
     day18::task2();
-}
\ No newline at end of file
+    // day18::task_big_integers(true);
+    // day18::task_benchmark();
+    // day18::task_file("homework.txt", true);
+}
+
+/// Generates a random input for a day (`aoc generate <day> [size] [seed]´)
+///
+/// What [size] means depends on the day (like the lines of a program or the rows of a map),
+/// by default the inputs are about as large as the ones of the tasks
+fn generate_input(args: &[String]) -> Result<String, String> {
+    let usage = "Usage: aoc generate <day> [size] [seed] (days are 2, 3, 12, 13, 14, 16, 17 and 18)";
+    let day = args.get(0).ok_or(usage)?.parse::<u32>().map_err(|_| usage)?;
+    let size = args.get(1).map(|size| size.parse::<usize>().map_err(|_| format!("Invalid size `{}´", size))).transpose()?;
+    let seed = args.get(2).map_or(Ok(1), |seed| seed.parse::<u64>().map_err(|_| format!("Invalid seed `{}´", seed)))?;
+
+    let input = match day {
+        2 => day02::generate_input(size.unwrap_or(1000), seed),
+        3 => day03::generate_input(size.unwrap_or(323), seed),
+        12 => day12::generate_input(size.unwrap_or(786), seed),
+        13 => day13::generate_input(size.unwrap_or(9), seed),
+        14 => day14::generate_input(size.unwrap_or(571), seed),
+        16 => day16::generate_input(size.unwrap_or(240), seed),
+        17 => day17::generate_input(size.unwrap_or(8), seed),
+        18 => day18::generate_input(size.unwrap_or(373), seed),
+        _ => return Err(usage.to_string())
+    };
+
+    Ok(input)
+}
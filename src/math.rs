@@ -0,0 +1,212 @@
+use num::{Integer, Signed};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Extended euclidean algorithm
+///
+/// returns (g, x, y) with `g = gcd(a, b) >= 0´ and `a * x + b * y = g´
+pub fn egcd<T: Integer + Signed + Copy>(a: T, b: T) -> (T, T, T) {
+    // iterative, s.t. the stack doesn't grow with the size of the numbers
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+
+    while !r.is_zero() {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r.is_negative() {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The inverse of [x] modulo [n] (i.e., `x * inverse = 1 (mod n)´) within `0..n´
+///
+/// returns [None] if there is none (i.e., [x] and [n] are not coprime)
+pub fn mod_inv<T: Integer + Signed + Copy>(x: T, n: T) -> Option<T> {
+    let (g, inverse, _) = egcd(x.mod_floor(&n), n);
+    if g.is_one() {
+        Some(inverse.mod_floor(&n))
+    } else {
+        None
+    }
+}
+
+/// Computes `base ^ exponent mod modulus´ (within `0..modulus´) by squaring
+///
+/// `(modulus - 1)²´ has to fit into [T], use a wider type (like [i128]) for large moduli
+pub fn mod_pow<T: Integer + Copy>(base: T, exponent: T, modulus: T) -> T {
+    assert!(exponent >= T::zero(), "The exponent must not be negative");
+    let two = T::one() + T::one();
+
+    let mut result = T::one().mod_floor(&modulus);
+    let mut base = base.mod_floor(&modulus);
+    let mut exponent = exponent;
+    while !exponent.is_zero() {
+        if exponent.is_odd() {
+            result = (result * base).mod_floor(&modulus);
+        }
+        base = (base * base).mod_floor(&modulus);
+        exponent = exponent / two;
+    }
+
+    result
+}
+
+/// Solves the system `x = residues[i] (mod moduli[i])´ (chinese remainder theorem)
+///
+/// The moduli don't have to be coprime. Returns the smallest non-negative solution together with
+/// the least common multiple of the moduli (every solution differs from it by a multiple of that)
+/// or [None] if the system has no solution. The least common multiple has to fit into [T]
+pub fn chinese_remainder<T: Integer + Signed + Copy>(residues: &[T], moduli: &[T]) -> Option<(T, T)> {
+    assert_eq!(residues.len(), moduli.len(), "Need exactly one modulus for each residue");
+
+    // merge the congruences one by one into `x = solution (mod lcm)´
+    let mut solution = T::zero();
+    let mut lcm = T::one();
+    for (residue, modulus) in residues.iter().zip(moduli.iter()) {
+        assert!(modulus.is_positive(), "The moduli have to be positive");
+        let residue = residue.mod_floor(modulus);
+
+        // solution + lcm * k = residue (mod modulus)
+        let (g, inverse, _) = egcd(lcm, *modulus);
+        let difference = residue - solution;
+        if !(difference.mod_floor(&g)).is_zero() {
+            return None;
+        }
+
+        let step = *modulus / g;
+        let k = ((difference / g).mod_floor(&step) * inverse.mod_floor(&step)).mod_floor(&step);
+        solution = solution + lcm * k;
+        lcm = lcm * step;
+        solution = solution.mod_floor(&lcm);
+    }
+
+    Some((solution, lcm))
+}
+
+/// Finds the smallest non-negative x with `base ^ x = target (mod modulus)´ (discrete logarithm)
+/// by the baby-step giant-step algorithm in about `sqrt(modulus)´ steps
+///
+/// [base] has to be coprime to [modulus] (otherwise [None] is returned as well as if there is
+/// no such x). Like for [mod_pow] `(modulus - 1)²´ has to fit into [T]
+pub fn discrete_log<T: Integer + Signed + Copy + Hash>(base: T, target: T, modulus: T) -> Option<T> {
+    assert!(modulus.is_positive(), "The modulus has to be positive");
+    let base = base.mod_floor(&modulus);
+    let target = target.mod_floor(&modulus);
+
+    if !egcd(base, modulus).0.is_one() {
+        return None;
+    }
+    if target == T::one().mod_floor(&modulus) {
+        return Some(T::zero());
+    }
+
+    // n_steps² >= modulus, s.t. every exponent below the modulus can be reached
+    let mut n_steps = T::one();
+    while n_steps * n_steps < modulus {
+        n_steps = n_steps + T::one();
+    }
+
+    // baby steps: remember target * base^j for 0 <= j < n_steps (the largest j wins,
+    // which gives the smallest x below)
+    let mut baby_steps: HashMap<T, T> = HashMap::new();
+    let mut value = target;
+    let mut j = T::zero();
+    while j < n_steps {
+        baby_steps.insert(value, j);
+        value = (value * base).mod_floor(&modulus);
+        j = j + T::one();
+    }
+
+    // giant steps: base^(i * n_steps) = target * base^j  =>  x = i * n_steps - j
+    let giant_step = mod_pow(base, n_steps, modulus);
+    let mut value = T::one();
+    let mut i = T::one();
+    while i <= n_steps {
+        value = (value * giant_step).mod_floor(&modulus);
+        if let Some(j) = baby_steps.get(&value) {
+            return Some(i * n_steps - *j);
+        }
+        i = i + T::one();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn gcd(a: i128, b: i128) -> i128 {
+        if b == 0 { a.abs() } else { gcd(b, a % b) }
+    }
+
+    proptest! {
+        #[test]
+        fn egcd_gives_bezout_coefficients(a in -1_000_000_000i64..1_000_000_000, b in -1_000_000_000i64..1_000_000_000) {
+            let (g, x, y) = egcd(a, b);
+            prop_assert_eq!(g as i128, gcd(a as i128, b as i128));
+            prop_assert_eq!(a as i128 * x as i128 + b as i128 * y as i128, g as i128);
+        }
+
+        #[test]
+        fn egcd_works_for_i32(a in any::<i16>(), b in any::<i16>()) {
+            let (g, x, y) = egcd(a as i32, b as i32);
+            prop_assert_eq!(g as i128, gcd(a as i128, b as i128));
+            prop_assert_eq!(a as i32 * x + b as i32 * y, g);
+        }
+
+        #[test]
+        fn mod_inv_is_inverse(x in -1_000_000_000i64..1_000_000_000, n in 2i64..1_000_000_000) {
+            match mod_inv(x, n) {
+                Some(inverse) => {
+                    prop_assert!(inverse >= 0 && inverse < n);
+                    prop_assert_eq!((x as i128 * inverse as i128).rem_euclid(n as i128), 1);
+                },
+                None => prop_assert_ne!(gcd(x as i128, n as i128), 1)
+            }
+        }
+
+        #[test]
+        fn mod_pow_equals_repeated_multiplication(base in any::<u32>(), exponent in 0u64..300, modulus in 1u64..(1 << 32)) {
+            let expected = (0..exponent).fold(1 % modulus, |power, _| power * (base as u64 % modulus) % modulus);
+            prop_assert_eq!(mod_pow(base as u64, exponent, modulus), expected);
+        }
+
+        #[test]
+        fn mod_pow_handles_negative_bases(base in -1000i64..1000, exponent in 0i64..50, modulus in 1i64..1000) {
+            let expected = (0..exponent).fold(1 % modulus, |power, _| (power * base).rem_euclid(modulus));
+            prop_assert_eq!(mod_pow(base, exponent, modulus), expected);
+        }
+
+        #[test]
+        fn chinese_remainder_finds_smallest_solution(
+            congruences in prop::collection::vec((-50i64..50, 1i64..25), 1..5)
+        ) {
+            let residues: Vec<i64> = congruences.iter().map(|(residue, _)| *residue).collect();
+            let moduli: Vec<i64> = congruences.iter().map(|(_, modulus)| *modulus).collect();
+            let lcm = moduli.iter().fold(1, |lcm, modulus| lcm / gcd(lcm as i128, *modulus as i128) as i64 * modulus);
+            let is_solution = |x: i64| residues.iter().zip(moduli.iter()).all(|(r, m)| (x - r).rem_euclid(*m) == 0);
+
+            let expected = (0..lcm).find(|x| is_solution(*x));
+            prop_assert_eq!(chinese_remainder(&residues, &moduli), expected.map(|x| (x, lcm)));
+        }
+
+        #[test]
+        fn discrete_log_finds_smallest_exponent(base in 0i64..2000, target in 0i64..2000, modulus in 1i64..2000) {
+            let expected = if gcd(base as i128, modulus as i128) == 1 {
+                (0..modulus).find(|x| mod_pow(base, *x, modulus) == target % modulus)
+            } else {
+                None
+            };
+            prop_assert_eq!(discrete_log(base, target, modulus), expected);
+        }
+    }
+}
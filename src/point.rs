@@ -0,0 +1,280 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+/// A point (or vector) with [N] integer coordinates
+///
+/// In 2d the first coordinate is x (east) and the second one y (north)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point<const N: usize>(pub [i64; N]);
+
+impl<const N: usize> Point<N> {
+    pub fn origin() -> Self {
+        Point([0; N])
+    }
+
+    /// Sum of the absolute differences in every dimension
+    pub fn manhattan_distance(&self, other: &Point<N>) -> i64 {
+        (0..N).map(|dim| (self.0[dim] - other.0[dim]).abs()).sum()
+    }
+
+    /// Largest absolute difference in any dimension (i.e., the moves of a chess king in 2d)
+    pub fn chebyshev_distance(&self, other: &Point<N>) -> i64 {
+        (0..N).map(|dim| (self.0[dim] - other.0[dim]).abs()).max().unwrap_or(0)
+    }
+
+    /// Creates the offsets to all points within a chebyshev distance of [radius]
+    /// (without the origin itself), ordered like [PointsInBox]
+    pub fn offsets_within(radius: i64) -> Vec<Point<N>> {
+        PointsInBox::new(Point([-radius; N]), Point([radius; N]))
+            .filter(|offset| *offset != Point::origin())
+            .collect()
+    }
+
+    /// Iterates over all 3^N - 1 points touching this one (by edge or corner)
+    pub fn neighbours(&self) -> impl Iterator<Item = Point<N>> {
+        let center = *self;
+        Point::<N>::offsets_within(1).into_iter().map(move |offset| center + offset)
+    }
+
+    /// Iterates over the 2 * N points differing by one in exactly one dimension
+    pub fn orthogonal_neighbours(&self) -> impl Iterator<Item = Point<N>> {
+        let center = *self;
+        (0..2 * N).map(move |i| {
+            let mut neighbour = center;
+            neighbour.0[i / 2] += if i % 2 == 0 { -1 } else { 1 };
+            neighbour
+        })
+    }
+}
+
+impl Point<2> {
+    pub fn new(x: i64, y: i64) -> Self {
+        Point([x, y])
+    }
+
+    pub fn x(&self) -> i64 {
+        self.0[0]
+    }
+
+    pub fn y(&self) -> i64 {
+        self.0[1]
+    }
+
+    /// Rotates by 90° counter clockwise around the origin
+    pub fn rotate_left(&self) -> Self {
+        Point::new(-self.y(), self.x())
+    }
+
+    /// Rotates by 90° clockwise around the origin
+    pub fn rotate_right(&self) -> Self {
+        Point::new(self.y(), -self.x())
+    }
+
+    /// Rotates by [degrees] around the origin (counter clockwise if positive)
+    ///
+    /// returns [None] if [degrees] is no multiple of 90
+    pub fn rotate(&self, degrees: i64) -> Option<Self> {
+        if degrees % 90 != 0 {
+            return None;
+        }
+
+        let n_quarters = (degrees / 90).rem_euclid(4);
+        Some((0..n_quarters).fold(*self, |point, _| point.rotate_left()))
+    }
+}
+
+/// Iterates over all points within the box spanned by [from] and [to] (both inclusive)
+///
+/// The first dimension changes slowest, the last one fastest (like an odometer)
+pub struct PointsInBox<const N: usize> {
+    from: Point<N>,
+    to: Point<N>,
+    next: Option<Point<N>>
+}
+
+impl<const N: usize> PointsInBox<N> {
+    pub fn new(from: Point<N>, to: Point<N>) -> Self {
+        let is_empty = (0..N).any(|dim| from.0[dim] > to.0[dim]);
+        let next = if is_empty { None } else { Some(from) };
+        PointsInBox { from, to, next }
+    }
+}
+
+impl<const N: usize> Iterator for PointsInBox<N> {
+    type Item = Point<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+
+        // Count up like an odometer: increase the last dimension and carry over on overflow
+        let mut following = current;
+        for dim in (0..N).rev() {
+            if following.0[dim] < self.to.0[dim] {
+                following.0[dim] += 1;
+                self.next = Some(following);
+                break;
+            }
+            following.0[dim] = self.from.0[dim];
+        }
+
+        Some(current)
+    }
+}
+
+impl<const N: usize> Add for Point<N> {
+    type Output = Point<N>;
+
+    fn add(mut self, other: Point<N>) -> Point<N> {
+        self += other;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for Point<N> {
+    fn add_assign(&mut self, other: Point<N>) {
+        for dim in 0..N {
+            self.0[dim] += other.0[dim];
+        }
+    }
+}
+
+impl<const N: usize> Sub for Point<N> {
+    type Output = Point<N>;
+
+    fn sub(mut self, other: Point<N>) -> Point<N> {
+        self -= other;
+        self
+    }
+}
+
+impl<const N: usize> SubAssign for Point<N> {
+    fn sub_assign(&mut self, other: Point<N>) {
+        for dim in 0..N {
+            self.0[dim] -= other.0[dim];
+        }
+    }
+}
+
+impl<const N: usize> Neg for Point<N> {
+    type Output = Point<N>;
+
+    fn neg(self) -> Point<N> {
+        self * -1
+    }
+}
+
+/// Scales every coordinate
+impl<const N: usize> Mul<i64> for Point<N> {
+    type Output = Point<N>;
+
+    fn mul(mut self, factor: i64) -> Point<N> {
+        for dim in 0..N {
+            self.0[dim] *= factor;
+        }
+        self
+    }
+}
+
+impl<const N: usize> Index<usize> for Point<N> {
+    type Output = i64;
+
+    fn index(&self, dim: usize) -> &i64 {
+        &self.0[dim]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Point<N> {
+    fn index_mut(&mut self, dim: usize) -> &mut i64 {
+        &mut self.0[dim]
+    }
+}
+
+/// Shows the point like `(1, -2, 3)´
+impl<const N: usize> fmt::Display for Point<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let coordinates: Vec<String> = self.0.iter().map(|v| v.to_string()).collect();
+        write!(f, "({})", coordinates.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        assert_eq!(Point::new(1, -2).manhattan_distance(&Point::new(-3, 4)), 10);
+        assert_eq!(Point::new(1, -2).chebyshev_distance(&Point::new(-3, 4)), 6);
+        assert_eq!(Point([1, 2, 3]).manhattan_distance(&Point([1, 2, 3])), 0);
+        assert_eq!(Point([0, 0, 0, 0]).manhattan_distance(&Point([1, -1, 1, -1])), 4);
+        assert_eq!(Point([0, 0, 0, 0]).chebyshev_distance(&Point([1, -1, 1, -1])), 1);
+    }
+
+    /// Checks the amounts and distances of the neighbours of a point in [N] dimensions
+    fn check_neighbours<const N: usize>(center: Point<N>) {
+        let neighbours: Vec<Point<N>> = center.neighbours().collect();
+        assert_eq!(neighbours.len(), 3usize.pow(N as u32) - 1);
+        assert!(neighbours.iter().all(|neighbour| center.chebyshev_distance(neighbour) == 1));
+
+        let orthogonal: Vec<Point<N>> = center.orthogonal_neighbours().collect();
+        assert_eq!(orthogonal.len(), 2 * N);
+        assert!(orthogonal.iter().all(|neighbour| center.manhattan_distance(neighbour) == 1));
+        assert!(orthogonal.iter().all(|neighbour| neighbours.contains(neighbour)));
+
+        // no point may show up twice
+        for points in [&neighbours, &orthogonal].iter() {
+            let mut sorted: Vec<[i64; N]> = points.iter().map(|point| point.0).collect();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), points.len());
+        }
+    }
+
+    #[test]
+    fn neighbours() {
+        check_neighbours(Point::new(3, -7));
+        check_neighbours(Point([0, 0, 0]));
+        check_neighbours(Point([1, -1, 5, 0]));
+        assert_eq!(Point::new(0, 0).orthogonal_neighbours().collect::<Vec<Point<2>>>(),
+                   vec![Point::new(-1, 0), Point::new(1, 0), Point::new(0, -1), Point::new(0, 1)]);
+    }
+
+    #[test]
+    fn rotates_in_quarters() {
+        let point = Point::new(1, 2);
+        assert_eq!(point.rotate_left(), Point::new(-2, 1));
+        assert_eq!(point.rotate_right(), Point::new(2, -1));
+        assert_eq!(point.rotate(90), Some(point.rotate_left()));
+        assert_eq!(point.rotate(-90), Some(point.rotate_right()));
+        assert_eq!(point.rotate(-360), Some(point));
+        assert_eq!(point.rotate(45), None);
+    }
+
+    #[test]
+    fn points_in_box_count_like_an_odometer() {
+        let points: Vec<Point<2>> = PointsInBox::new(Point::new(-1, 0), Point::new(0, 2)).collect();
+        assert_eq!(points, vec![Point::new(-1, 0), Point::new(-1, 1), Point::new(-1, 2),
+                                Point::new(0, 0), Point::new(0, 1), Point::new(0, 2)]);
+
+        let points: Vec<Point<4>> = PointsInBox::new(Point([0, -1, 3, 0]), Point([1, 1, 3, 1])).collect();
+        assert_eq!(points.len(), 2 * 3 * 1 * 2);
+        assert_eq!(points.first(), Some(&Point([0, -1, 3, 0])));
+        assert_eq!(points.last(), Some(&Point([1, 1, 3, 1])));
+        assert!(points.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn points_in_edge_and_corner_boxes() {
+        // a single point (a corner of a larger box)
+        let corner = Point([2, -2, 5]);
+        assert_eq!(PointsInBox::new(corner, corner).collect::<Vec<Point<3>>>(), vec![corner]);
+
+        // an edge, only one dimension spans more than a single coordinate
+        let edge: Vec<Point<3>> = PointsInBox::new(Point([2, -2, 5]), Point([2, 0, 5])).collect();
+        assert_eq!(edge, vec![Point([2, -2, 5]), Point([2, -1, 5]), Point([2, 0, 5])]);
+
+        // empty if [from] is beyond [to] in any dimension
+        assert_eq!(PointsInBox::new(Point::new(0, 1), Point::new(3, 0)).count(), 0);
+        assert_eq!(PointsInBox::new(Point([0, 0, 0, 1]), Point([3, 3, 3, 0])).count(), 0);
+    }
+}